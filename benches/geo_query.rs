@@ -0,0 +1,97 @@
+//! # Geo Query Benchmark
+//!
+//! Measures lookup throughput of the geo index for exact names, misspelled names
+//! (fuzzy fallback) and coordinates (nearest cities).
+//!
+//! ```text
+//! cargo bench --bench geo_query
+//! GEO_DATA=/path/to/cities15000.txt cargo bench --bench geo_query
+//! ```
+
+use anyhow::Result;
+use rdns_toys::services::geo::{Geo, NameSources};
+use std::hint::black_box;
+use std::path::Path;
+use std::time::{Duration, Instant};
+use std::{env, fs};
+
+/// Minimum time spent on each scenario
+const MIN_DURATION: Duration = Duration::from_secs(2);
+
+/// Runs every query in rounds for at least [`MIN_DURATION`] and prints the throughput.
+fn measure(label: &str, queries: &[String], lookup: impl Fn(&str) -> usize) {
+    if queries.is_empty() {
+        return;
+    }
+
+    let started = Instant::now();
+    let mut rounds = 0;
+    let mut found = 0;
+    while rounds == 0 || started.elapsed() < MIN_DURATION {
+        for query in queries {
+            found += lookup(black_box(query));
+        }
+        rounds += 1;
+    }
+    let elapsed = started.elapsed().as_secs_f64();
+    let lookups = rounds * queries.len();
+
+    println!(
+        "{:<10} {:>12.0} {:>12.2} {:>10}",
+        label,
+        lookups as f64 / elapsed,
+        elapsed * 1e9 / lookups as f64 / 1000.0,
+        found / rounds
+    );
+}
+
+fn main() -> Result<()> {
+    let data_path = env::var("GEO_DATA").unwrap_or_else(|_| "data/cities15000.txt".to_string());
+    if !Path::new(&data_path).exists() {
+        println!(
+            "Skipping geo query benchmark: {} not found (set GEO_DATA)",
+            data_path
+        );
+        return Ok(());
+    }
+
+    let sources = NameSources {
+        inline: true,
+        ..NameSources::default()
+    };
+    let geo = Geo::new(&data_path, &sources)?;
+
+    // Every 10th city name, the same names with their second letter dropped, and coordinates
+    let rows = fs::read_to_string(&data_path)?;
+    let sample: Vec<Vec<&str>> = rows
+        .lines()
+        .step_by(10)
+        .map(|line| line.split('\t').collect())
+        .filter(|fields: &Vec<&str>| fields.len() == 19)
+        .collect();
+    let exact: Vec<String> = sample.iter().map(|f| f[2].to_lowercase()).collect();
+    let fuzzy: Vec<String> = exact
+        .iter()
+        .filter(|name| name.len() > 4)
+        .map(|name| name.chars().take(1).chain(name.chars().skip(2)).collect())
+        .collect();
+    let coordinates: Vec<String> = sample
+        .iter()
+        .map(|f| format!("{},{}", f[4], f[5]))
+        .collect();
+
+    println!("Geo query throughput ({} locations):", geo.count());
+    println!(
+        "{:<10} {:>12} {:>12} {:>10}",
+        "scenario", "lookups/s", "us/lookup", "results"
+    );
+    measure("exact", &exact, |q| geo.query(q).map_or(0, |r| r.len()));
+    measure("fuzzy", &fuzzy, |q| geo.query(q).map_or(0, |r| r.len()));
+    measure("nearest", &coordinates, |q| {
+        let (lat, lon) = q.split_once(',').unwrap_or_default();
+        let (lat, lon) = (lat.parse().unwrap_or(0.0), lon.parse().unwrap_or(0.0));
+        geo.nearest(lat, lon, 5).len()
+    });
+
+    Ok(())
+}
@@ -0,0 +1,140 @@
+//! # Geo Startup Benchmark
+//!
+//! Compares the start time and memory use of building the geo index from the geonames
+//! TSV file against loading it from a binary snapshot.
+//!
+//! Each mode runs in a fresh child process so the resident set sizes do not mix.
+//!
+//! ```text
+//! cargo bench --bench geo_startup
+//! GEO_DATA=/path/to/cities15000.txt cargo bench --bench geo_startup
+//! ```
+
+use anyhow::{Context, Result, bail};
+use rdns_toys::services::geo::{Geo, NameSources};
+use std::path::Path;
+use std::process::Command;
+use std::time::Instant;
+use std::{env, fs};
+
+/// Environment variable selecting the child process mode
+const MODE_VAR: &str = "GEO_BENCH_MODE";
+
+/// Builds the index the same way the default configuration does.
+fn sources() -> NameSources {
+    NameSources {
+        inline: true,
+        ..NameSources::default()
+    }
+}
+
+/// Reads a field of `/proc/self/status` in kilobytes (Linux only).
+fn proc_status_kb(field: &str) -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix(field))?
+        .trim_start_matches(':')
+        .trim()
+        .trim_end_matches("kB")
+        .trim()
+        .parse()
+        .ok()
+}
+
+/// Loads the index in the requested mode and prints "elapsed_ms rss_kb peak_kb locations".
+fn run_child(mode: &str, data_path: &str, snapshot_path: &str) -> Result<()> {
+    let started = Instant::now();
+    let geo = match mode {
+        "tsv" => Geo::new(data_path, &sources())?,
+        "snapshot" | "write" => Geo::open(data_path, &sources(), Some(snapshot_path))?,
+        _ => bail!("Unknown mode '{}'", mode),
+    };
+    let elapsed = started.elapsed();
+
+    println!(
+        "{:.1} {} {} {}",
+        elapsed.as_secs_f64() * 1000.0,
+        proc_status_kb("VmRSS").unwrap_or(0),
+        proc_status_kb("VmHWM").unwrap_or(0),
+        geo.count()
+    );
+    Ok(())
+}
+
+/// Runs one mode in a child process and returns its output fields.
+fn spawn(mode: &str, data_path: &str, snapshot_path: &str) -> Result<Vec<String>> {
+    let output = Command::new(env::current_exe()?)
+        .env(MODE_VAR, mode)
+        .env("GEO_DATA", data_path)
+        .env("GEO_SNAPSHOT", snapshot_path)
+        .output()
+        .with_context(|| format!("Failed to run the '{}' benchmark", mode))?;
+
+    if !output.status.success() {
+        bail!(
+            "The '{}' benchmark failed: {}",
+            mode,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    Ok(String::from_utf8(output.stdout)?
+        .split_whitespace()
+        .map(String::from)
+        .collect())
+}
+
+fn main() -> Result<()> {
+    let data_path = env::var("GEO_DATA").unwrap_or_else(|_| "data/cities15000.txt".to_string());
+    let snapshot_path = env::var("GEO_SNAPSHOT").unwrap_or_else(|_| {
+        env::temp_dir()
+            .join("rdns-toys-geo-bench.snapshot")
+            .to_string_lossy()
+            .to_string()
+    });
+
+    if let Ok(mode) = env::var(MODE_VAR) {
+        return run_child(&mode, &data_path, &snapshot_path);
+    }
+
+    if !Path::new(&data_path).exists() {
+        println!(
+            "Skipping geo startup benchmark: {} not found (set GEO_DATA)",
+            data_path
+        );
+        return Ok(());
+    }
+
+    // Start from a fresh snapshot so the "snapshot" run never rebuilds
+    let _ = fs::remove_file(&snapshot_path);
+    spawn("write", &data_path, &snapshot_path)?;
+    let snapshot_size = fs::metadata(&snapshot_path)?.len();
+
+    println!("Geo index startup ({}):", data_path);
+    println!(
+        "{:<10} {:>12} {:>12} {:>12} {:>10}",
+        "mode", "start (ms)", "RSS (MiB)", "peak (MiB)", "locations"
+    );
+    for mode in ["tsv", "snapshot"] {
+        let fields = spawn(mode, &data_path, &snapshot_path)?;
+        let [elapsed, rss, peak, count] = fields.as_slice() else {
+            bail!("Unexpected output from the '{}' benchmark", mode);
+        };
+        let mib = |kb: &String| kb.parse::<f64>().unwrap_or(0.0) / 1024.0;
+        println!(
+            "{:<10} {:>12} {:>12.1} {:>12.1} {:>10}",
+            mode,
+            elapsed,
+            mib(rss),
+            mib(peak),
+            count
+        );
+    }
+    println!(
+        "Snapshot size: {:.1} MiB",
+        snapshot_size as f64 / 1024.0 / 1024.0
+    );
+
+    fs::remove_file(&snapshot_path)?;
+    Ok(())
+}
@@ -0,0 +1,435 @@
+//! # Configuration
+//!
+//! This module loads the server configuration from a TOML file. Every section
+//! has sensible defaults, so a missing file (or a partially filled one) still
+//! yields a working server on `localhost:8053`. The default zone leaves out
+//! services that need data files besides `data/cities15000.txt` (country, ifsc,
+//! micr and bank), so those must be enabled explicitly.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+use crate::handlers::ERROR_TTL;
+use crate::services::HELP_TTL;
+use crate::services::aerial::AERIAL_TTL;
+use crate::services::country::COUNTRY_TTL;
+use crate::services::geo::{GEO_NEAREST_RESULTS, GEO_TTL};
+use crate::services::ifsc::{IFSC_MAX_RESULTS, IFSC_TTL};
+use crate::services::ip::IP_TTL;
+use crate::services::pi::PI_TTL;
+use crate::services::random::RANDOM_TTL;
+use crate::services::sun::SUN_TTL;
+use crate::services::time::TIME_TTL;
+use crate::services::uuid::UUID_TTL;
+
+/// Top-level configuration for the rdns-toys server.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Network settings for the DNS listener
+    pub server: ServerConfig,
+    /// Authoritative zones served by this instance
+    pub zones: Vec<ZoneConfig>,
+    /// Settings for the ip service
+    pub ip: IpConfig,
+    /// Settings for the pi service
+    pub pi: PiConfig,
+    /// Settings for the random service
+    pub random: RandomConfig,
+    /// Settings for the geo service
+    pub geo: GeoConfig,
+    /// Settings for the uuid service
+    pub uuid: UuidConfig,
+    /// Settings for the time service
+    pub time: TimeConfig,
+    /// Settings for the aerial service
+    pub aerial: AerialConfig,
+    /// Settings for the sun service
+    pub sun: SunConfig,
+    /// Settings for the country service
+    pub country: CountryConfig,
+    /// Settings for the ifsc service
+    pub ifsc: IfscConfig,
+}
+
+/// Network settings for the DNS listener.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ServerConfig {
+    /// IP address to bind the UDP socket to
+    pub address: String,
+    /// UDP port to listen on
+    pub port: u16,
+}
+
+/// A single authoritative zone and the services it exposes.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ZoneConfig {
+    /// Zone apex (e.g., "dns.toys" or "toys.internal")
+    pub domain: String,
+    /// Service suffixes enabled in this zone (e.g., ["ip", "geo"])
+    pub services: Vec<String>,
+    /// Extra help lines appended after the generated service list
+    pub help: Vec<String>,
+    /// TTL of the help records
+    pub help_ttl: u32,
+    /// TTL of `error:` records, kept short so mistakes are not cached
+    pub error_ttl: u32,
+    /// Start of authority values for this zone
+    pub soa: SoaConfig,
+}
+
+/// Start of authority (SOA) values for a zone.
+///
+/// `mname` and `rname` default to `ns.<zone>` and `hostmaster.<zone>` when left empty.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SoaConfig {
+    /// Primary name server for the zone
+    pub mname: Option<String>,
+    /// Responsible mailbox, encoded as a domain name
+    pub rname: Option<String>,
+    /// Zone serial number
+    pub serial: u32,
+    /// Seconds before secondaries should refresh the zone
+    pub refresh: i32,
+    /// Seconds before a failed refresh is retried
+    pub retry: i32,
+    /// Seconds after which secondaries stop answering for the zone
+    pub expire: i32,
+    /// Minimum TTL field of the SOA record, used as the zone's negative caching TTL
+    pub minimum: u32,
+    /// TTL of the SOA record itself
+    pub ttl: u32,
+}
+
+/// Settings for the ip service.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct IpConfig {
+    /// Default TTL of ip answers
+    pub ttl: u32,
+    /// TTL overrides per record type (e.g., `TXT = 300`)
+    pub record_ttls: HashMap<String, u32>,
+}
+
+/// Settings for the pi service.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PiConfig {
+    /// Default TTL of pi answers
+    pub ttl: u32,
+    /// TTL overrides per record type (e.g., `TXT = 300`)
+    pub record_ttls: HashMap<String, u32>,
+}
+
+/// Settings for the random service.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct RandomConfig {
+    /// Default TTL of random answers
+    pub ttl: u32,
+    /// TTL overrides per record type (e.g., `TXT = 300`)
+    pub record_ttls: HashMap<String, u32>,
+}
+
+/// Settings for the geo service.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GeoConfig {
+    /// Path to the geonames.org cities file
+    pub data_path: String,
+    /// Index the `alternatenames` column of the cities file (e.g., "Bombay" for Mumbai)
+    pub alternate_names: bool,
+    /// Optional geonames `alternateNamesV2.txt` file, used instead of the
+    /// `alternatenames` column so names can be filtered by language
+    pub alternate_names_path: Option<String>,
+    /// ISO language codes kept from `alternate_names_path` (e.g., ["en", "de", "hi"]);
+    /// empty keeps every language
+    pub languages: Vec<String>,
+    /// Optional geonames `admin1CodesASCII.txt` file, used to show region names
+    /// instead of admin1 codes
+    pub admin1_path: Option<String>,
+    /// Optional binary snapshot of the built index, loaded instead of parsing the data
+    /// files and rewritten whenever they change
+    pub snapshot_path: Option<String>,
+    /// Maximum number of locations returned for a coordinate query
+    pub nearest_results: usize,
+    /// Show full country names in TXT answers, read from [`CountryConfig::data_path`]
+    pub country_names: bool,
+    /// Default TTL of geo answers
+    pub ttl: u32,
+    /// TTL overrides per record type (e.g., `TXT = 300`)
+    pub record_ttls: HashMap<String, u32>,
+}
+
+/// Settings for the uuid service.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct UuidConfig {
+    /// Maximum number of UUIDs returned for a single query
+    pub max_results: usize,
+    /// Default TTL of uuid answers
+    pub ttl: u32,
+    /// TTL overrides per record type (e.g., `TXT = 300`)
+    pub record_ttls: HashMap<String, u32>,
+}
+
+/// Settings for the time service.
+///
+/// The time service also serves the `tz` conversion suffix, and reads locations from the
+/// geo data file configured in [`GeoConfig`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TimeConfig {
+    /// Default TTL of time answers
+    pub ttl: u32,
+    /// TTL overrides per record type (e.g., `TXT = 300`)
+    pub record_ttls: HashMap<String, u32>,
+}
+
+/// Settings for the aerial service.
+///
+/// City names are resolved with the geo data file configured in [`GeoConfig`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AerialConfig {
+    /// Default TTL of aerial answers
+    pub ttl: u32,
+    /// TTL overrides per record type (e.g., `TXT = 300`)
+    pub record_ttls: HashMap<String, u32>,
+}
+
+/// Settings for the sun service.
+///
+/// Locations are resolved with the geo data file configured in [`GeoConfig`].
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SunConfig {
+    /// Default TTL of sun answers
+    pub ttl: u32,
+    /// TTL overrides per record type (e.g., `TXT = 300`)
+    pub record_ttls: HashMap<String, u32>,
+}
+
+/// Settings for the country service.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CountryConfig {
+    /// Path to the geonames.org `countryInfo.txt` file
+    pub data_path: String,
+    /// Default TTL of country answers
+    pub ttl: u32,
+    /// TTL overrides per record type (e.g., `TXT = 300`)
+    pub record_ttls: HashMap<String, u32>,
+}
+
+/// Settings for the ifsc service.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct IfscConfig {
+    /// Directory of Razorpay IFSC JSON files, one per bank (e.g., `SBIN.json`), or a
+    /// `.tar.gz`/`.tgz` or `.zip` archive of them (e.g., the released `by-bank.tar.gz`)
+    pub data_path: String,
+    /// Maximum number of branches listed for a MICR or bank and city query
+    pub max_results: usize,
+    /// Default TTL of ifsc answers
+    pub ttl: u32,
+    /// TTL overrides per record type (e.g., `TXT = 300`)
+    pub record_ttls: HashMap<String, u32>,
+}
+
+impl Config {
+    /// Loads the configuration from a TOML file.
+    ///
+    /// If the file does not exist, the default configuration is returned so the
+    /// server can still be started without any setup.
+    ///
+    /// # Arguments
+    /// * `path` - Path to the TOML configuration file
+    ///
+    /// # Returns
+    /// * `Ok(Config)` - The parsed (or default) configuration
+    /// * `Err(anyhow::Error)` - If the file exists but cannot be read or parsed
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        if !path.exists() {
+            tracing::info!("Config file {} not found, using defaults", path.display());
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Error reading config file {}", path.display()))?;
+        let config: Config = toml::from_str(&content)
+            .with_context(|| format!("Error parsing config file {}", path.display()))?;
+        Ok(config)
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            server: ServerConfig::default(),
+            zones: vec![ZoneConfig::default()],
+            ip: IpConfig::default(),
+            pi: PiConfig::default(),
+            random: RandomConfig::default(),
+            geo: GeoConfig::default(),
+            uuid: UuidConfig::default(),
+            time: TimeConfig::default(),
+            aerial: AerialConfig::default(),
+            sun: SunConfig::default(),
+            country: CountryConfig::default(),
+            ifsc: IfscConfig::default(),
+        }
+    }
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1".to_string(),
+            port: 8053,
+        }
+    }
+}
+
+impl Default for ZoneConfig {
+    fn default() -> Self {
+        Self {
+            domain: "localhost".to_string(),
+            services: [
+                "ip", "uuid", "pi", "geo", "time", "tz", "aerial", "sun", "random",
+            ]
+            .iter()
+            .map(|s| s.to_string())
+            .collect(),
+            help: Vec::new(),
+            help_ttl: HELP_TTL,
+            error_ttl: ERROR_TTL,
+            soa: SoaConfig::default(),
+        }
+    }
+}
+
+impl Default for SoaConfig {
+    fn default() -> Self {
+        Self {
+            mname: None,
+            rname: None,
+            serial: 1,
+            refresh: 3600,
+            retry: 600,
+            expire: 86400,
+            minimum: 60,
+            ttl: 3600,
+        }
+    }
+}
+
+impl Default for IpConfig {
+    fn default() -> Self {
+        Self {
+            ttl: IP_TTL,
+            record_ttls: HashMap::new(),
+        }
+    }
+}
+
+impl Default for PiConfig {
+    fn default() -> Self {
+        Self {
+            ttl: PI_TTL,
+            record_ttls: HashMap::new(),
+        }
+    }
+}
+
+impl Default for RandomConfig {
+    fn default() -> Self {
+        Self {
+            ttl: RANDOM_TTL,
+            record_ttls: HashMap::new(),
+        }
+    }
+}
+
+impl Default for GeoConfig {
+    fn default() -> Self {
+        Self {
+            data_path: "data/cities15000.txt".to_string(),
+            alternate_names: true,
+            alternate_names_path: None,
+            languages: Vec::new(),
+            admin1_path: None,
+            snapshot_path: None,
+            nearest_results: GEO_NEAREST_RESULTS,
+            country_names: false,
+            ttl: GEO_TTL,
+            record_ttls: HashMap::new(),
+        }
+    }
+}
+
+impl Default for UuidConfig {
+    fn default() -> Self {
+        Self {
+            max_results: 10,
+            ttl: UUID_TTL,
+            record_ttls: HashMap::new(),
+        }
+    }
+}
+
+impl Default for TimeConfig {
+    fn default() -> Self {
+        Self {
+            ttl: TIME_TTL,
+            record_ttls: HashMap::new(),
+        }
+    }
+}
+
+impl Default for AerialConfig {
+    fn default() -> Self {
+        Self {
+            ttl: AERIAL_TTL,
+            record_ttls: HashMap::new(),
+        }
+    }
+}
+
+impl Default for SunConfig {
+    fn default() -> Self {
+        Self {
+            ttl: SUN_TTL,
+            record_ttls: HashMap::new(),
+        }
+    }
+}
+
+impl Default for CountryConfig {
+    fn default() -> Self {
+        Self {
+            data_path: "data/countryInfo.txt".to_string(),
+            ttl: COUNTRY_TTL,
+            record_ttls: HashMap::new(),
+        }
+    }
+}
+
+impl Default for IfscConfig {
+    fn default() -> Self {
+        Self {
+            data_path: "data/ifsc".to_string(),
+            max_results: IFSC_MAX_RESULTS,
+            ttl: IFSC_TTL,
+            record_ttls: HashMap::new(),
+        }
+    }
+}
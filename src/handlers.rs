@@ -1,6 +1,8 @@
 use std::collections::HashMap;
 use std::iter;
+use std::net::{Ipv4Addr, Ipv6Addr};
 use std::str::FromStr;
+use std::sync::Arc;
 
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
@@ -9,15 +11,49 @@ use regex::Regex;
 use tracing;
 
 use hickory_proto::{
-    op::{Header, OpCode},
-    rr::{LowerName, Name, RData, Record, RecordType, rdata},
+    op::{Edns, Header, OpCode, ResponseCode},
+    rr::{
+        LowerName, Name, RData, Record, RecordType, rdata,
+        rdata::opt::{ClientSubnet, EdnsCode, EdnsOption},
+    },
 };
 use hickory_server::{
     authority::MessageResponseBuilder,
     server::{Request, RequestHandler, ResponseHandler, ResponseInfo},
 };
 
-use crate::services;
+/// Maximum length in bytes of a single TXT character-string (RFC 1035, section 3.3).
+pub const MAX_TXT_STRING_LEN: usize = 255;
+
+/// Default TTL of error responses, kept short so mistakes are not cached.
+pub const ERROR_TTL: u32 = 1;
+
+/// EDNS version this server implements (RFC 6891).
+const EDNS_VERSION: u8 = 0;
+
+/// UDP payload size advertised in EDNS responses, the DNS Flag Day 2020 size that
+/// avoids IP fragmentation. It is the server's own limit, whatever the client offers.
+const EDNS_PAYLOAD: u16 = 1232;
+
+/// Record type code of LOC records (RFC 1876), not natively supported by hickory.
+pub const LOC_RECORD_TYPE: u16 = 29;
+
+/// Record type code of URI records (RFC 7553), not natively supported by hickory.
+pub const URI_RECORD_TYPE: u16 = 256;
+
+/// Record types that are routed to services; everything else gets an empty answer.
+const SERVICE_RECORD_TYPES: [RecordType; 5] = [
+    RecordType::TXT,
+    RecordType::A,
+    RecordType::AAAA,
+    RecordType::Unknown(LOC_RECORD_TYPE),
+    RecordType::Unknown(URI_RECORD_TYPE),
+];
+
+// RFC 1876 defaults for LOC records, in meters
+const LOC_DEFAULT_SIZE: f64 = 1.0;
+const LOC_DEFAULT_HORIZ_PRE: f64 = 10_000.0;
+const LOC_DEFAULT_VERT_PRE: f64 = 10.0;
 
 // --- Regex for cleaning Queries ---//
 static RE_CLEAN: Lazy<Regex> =
@@ -36,7 +72,7 @@ pub trait Service: Send + Sync {
     /// * `cleaned_query` - The cleaned query string (for text-based services)
     ///
     /// ## Returns
-    /// * `Some(Vec<Record>)` - Vector of DNS records to return
+    /// * `Some(Answer)` - The DNS records to return, flagged when they explain an error
     /// * `None` - If the service doesn't support this query/record type
     async fn query(
         &self,
@@ -44,62 +80,193 @@ pub trait Service: Send + Sync {
         query_name: &Name,
         query_type: RecordType,
         cleaned_query: &str,
-    ) -> Option<Vec<Record>>;
+    ) -> Option<Answer>;
 
     /// Export raw service data for debugging or monitoring.
     async fn dump(&self) -> Result<Vec<u8>>;
+
+    /// Returns the scope prefix length to echo for an EDNS Client Subnet (RFC 7871).
+    ///
+    /// The scope tells ECS-aware resolvers how much of the client subnet the answer
+    /// depends on, and so for which clients they may reuse it from their cache. Most
+    /// answers do not depend on the client at all, so the default is 0.
+    ///
+    /// ## Arguments
+    /// * `query_type` - The type of DNS record requested
+    /// * `subnet` - The client subnet sent with the query
+    fn client_subnet_scope(&self, _query_type: RecordType, _subnet: &ClientSubnet) -> u8 {
+        0
+    }
 }
 
-pub struct DnsHandlers {
-    pub services: HashMap<String, Box<dyn Service>>, // Mapping from DNS query suffix (e.g., "ip", "pi") to the corresponding service handler.
-    pub domain: LowerName, // The authoritative domain for which this handler is responsible.
-    pub help_records: Vec<Record>, // Pre-generated TXT records describing available DNS services and usage.
+/// TTLs of a service's answers: a default plus optional overrides per record type.
+///
+/// Operators tune these in the configuration to control how long recursive resolvers
+/// cache each kind of answer, e.g. a long TTL for geo LOC records but a short one for TXT.
+#[derive(Debug, Clone, Default)]
+pub struct Ttls {
+    default: u32,
+    by_type: HashMap<RecordType, u32>,
 }
 
-impl DnsHandlers {
-    /// Creates a new DnsHandlers instance with the specified authoritative domain.
-    ///
-    /// This constructor initializes the service registry and generates help records
-    /// that describe available services to users. The help records provide usage
-    /// examples and instructions for accessing different services.
+impl Ttls {
+    /// Creates TTLs that use the same value for every record type.
+    pub fn new(default: u32) -> Self {
+        Self {
+            default,
+            by_type: HashMap::new(),
+        }
+    }
+
+    /// Creates TTLs from configuration values.
     ///
     /// ## Arguments
-    /// * `domain` - The authoritative domain this handler will manage
+    /// * `default` - TTL used for record types without an override
+    /// * `overrides` - TTLs keyed by record type name (e.g., "TXT", "A", "LOC")
     ///
     /// ## Returns
-    /// * `Ok(DnsHandlers)` - A fully initialized handler instance
-    /// * `Err(anyhow::Error)` - If help record generation fails
-    pub fn new(domain: LowerName) -> Result<Self> {
-        let help_records = services::create_help_records(&domain.to_string())?;
-        Ok(DnsHandlers {
-            services: HashMap::new(),
+    /// * `Ok(Ttls)` - The parsed TTLs
+    /// * `Err(anyhow::Error)` - If a record type name is not recognised
+    pub fn from_config(default: u32, overrides: &HashMap<String, u32>) -> Result<Self> {
+        let mut ttls = Self::new(default);
+        for (name, ttl) in overrides {
+            let record_type = parse_record_type(name)
+                .ok_or_else(|| anyhow!("Unknown record type '{}' in TTL overrides", name))?;
+            ttls.by_type.insert(record_type, *ttl);
+        }
+        Ok(ttls)
+    }
+
+    /// Returns the TTL for answers of the given record type.
+    pub fn get(&self, record_type: RecordType) -> u32 {
+        self.by_type
+            .get(&record_type)
+            .copied()
+            .unwrap_or(self.default)
+    }
+}
+
+/// Parses a record type name, including the LOC and URI types hickory does not know about.
+pub fn parse_record_type(name: &str) -> Option<RecordType> {
+    match name.to_uppercase().as_str() {
+        "LOC" => Some(RecordType::Unknown(LOC_RECORD_TYPE)),
+        "URI" => Some(RecordType::Unknown(URI_RECORD_TYPE)),
+        other => RecordType::from_str(other).ok(),
+    }
+}
+
+/// Records a service answers a single query with.
+#[derive(Debug, Clone, Default)]
+pub struct Answer {
+    pub records: Vec<Record>, // Records for the answer section.
+    pub is_error: bool, // Set by `DnsHandlers::create_error_response`; errors get the zone's error TTL.
+}
+
+impl From<Vec<Record>> for Answer {
+    fn from(records: Vec<Record>) -> Self {
+        Self {
+            records,
+            is_error: false,
+        }
+    }
+}
+
+/// Answer and authority records produced for a request.
+#[derive(Debug, Clone, Default)]
+pub struct DnsResponse {
+    pub answers: Vec<Record>,                // Records for the answer section.
+    pub authority: Vec<Record>, // Records for the authority section (the SOA of negative answers).
+    pub client_subnet: Option<ClientSubnet>, // EDNS Client Subnet to echo, with the scope of the answer.
+}
+
+impl DnsResponse {
+    /// Creates a response that only carries answers.
+    pub fn answers(answers: Vec<Record>) -> Self {
+        Self {
+            answers,
+            ..Default::default()
+        }
+    }
+}
+
+/// An authoritative zone together with the services enabled in it.
+///
+/// A single server instance can serve several zones (e.g. an internal and a public name),
+/// each with its own service set, help text and SOA record.
+pub struct Zone {
+    pub domain: LowerName, // The zone apex this zone is authoritative for.
+    pub services: HashMap<String, Arc<dyn Service>>, // Mapping from DNS query suffix (e.g., "ip", "pi") to the corresponding service handler.
+    pub help_records: Vec<Record>, // Pre-generated TXT records describing available DNS services and usage.
+    pub soa: Record, // Start of authority record returned for SOA queries at the zone apex.
+    pub negative_soa: Record, // SOA record added to the authority section of empty answers.
+    pub error_ttl: u32, // TTL applied to ``error:`` TXT records answered by this zone.
+}
+
+impl Zone {
+    /// Creates a new zone with the specified apex and SOA record.
+    ///
+    /// The zone starts without any services or help records; use [`Zone::register`]
+    /// and [`Zone::set_help_records`] to populate it.
+    ///
+    /// The SOA ``minimum`` field is the zone's negative TTL (RFC 2308): empty answers carry
+    /// the SOA in the authority section with a TTL of ``min(soa_ttl, minimum)``, which tells
+    /// recursive resolvers how long to cache the absence of data.
+    ///
+    /// ## Arguments
+    /// * `domain` - The authoritative domain this zone will manage
+    /// * `soa` - The SOA rdata describing the zone
+    /// * `soa_ttl` - TTL of the SOA record
+    pub fn new(domain: LowerName, soa: rdata::SOA, soa_ttl: u32) -> Self {
+        let negative_ttl = soa_ttl.min(soa.minimum());
+        let soa = Record::from_rdata(Name::from(domain.clone()), soa_ttl, RData::SOA(soa));
+        let mut negative_soa = soa.clone();
+        negative_soa.set_ttl(negative_ttl);
+        Zone {
             domain,
-            help_records,
-        })
+            services: HashMap::new(),
+            help_records: Vec::new(),
+            soa,
+            negative_soa,
+            error_ttl: ERROR_TTL,
+        }
     }
 
     /// Registers a new DNS service with the given suffix.
     ///
-    /// This method adds a service implementation to the registry, making it available
+    /// This method adds a service implementation to the zone, making it available
     /// for handling DNS queries that match the specified suffix. Once registered,
     /// queries like "mumbai.time.example.com" will be routed to the service
     /// registered with suffix "time".
     ///
     /// This is the "plugin" mechanism - it allows new services to be added to the
     /// DNS server dynamically. Each service becomes available at its own subdomain
-    /// within the authoritative domain.
+    /// within the authoritative domain. The same service instance can be shared
+    /// between several zones.
     ///
     /// ## Arguments
     /// * `suffix` - The DNS query suffix (e.g., "ip", "pi", "time") to associate with the service
-    /// * `service` - A boxed implementation of the Service trait to handle queries for this suffix
+    /// * `service` - A shared implementation of the Service trait to handle queries for this suffix
     ///
     /// ## Example
+    /// ```ignore
+    /// let mut zone = Zone::new(domain, soa, 3600);
+    /// zone.register("ip".to_string(), Arc::new(IpService::new()));
     /// ```
-    /// let mut handlers = DnsHandlers::new(domain)?;
-    /// handlers.register("ip".to_string(), Box::new(IpService::new()));
-    /// ```
-    pub fn register(&mut self, suffix: String, service: Box<dyn Service>) {
-        self.services.insert(suffix.clone(), service);
+    pub fn register(&mut self, suffix: String, service: Arc<dyn Service>) {
+        self.services.insert(suffix, service);
+    }
+
+    /// Replaces the help records returned for `help.<zone>` queries.
+    pub fn set_help_records(&mut self, help_records: Vec<Record>) {
+        self.help_records = help_records;
+    }
+
+    /// Sets the TTL of the error records this zone answers with.
+    ///
+    /// Services build error answers with [`DnsHandlers::create_error_response`] and the
+    /// default [`ERROR_TTL`]; the zone rewrites the TTL of answers flagged as errors.
+    pub fn set_error_ttl(&mut self, error_ttl: u32) {
+        self.error_ttl = error_ttl;
     }
 
     /// Routes service requests to the correct service implementation and formats the DNS response.
@@ -107,7 +274,7 @@ impl DnsHandlers {
     /// This function is the main dynamic DNS service router. It:
     /// - Validates that the request is a standard query (``OpCode::Query``)
     /// - Enforces a maximum of 5 queries per request to prevent abuse
-    /// - Iterates over each question, only processing ``TXT`` and ``A`` record types
+    /// - Iterates over each question, only processing record types services can answer (``SERVICE_RECORD_TYPES``)
     /// - Extracts the relevant query portion by removing the service suffix and domain
     /// - Looks up the registered service for the given suffix and invokes its async ``query`` method
     /// - Converts the service's response into DNS records, setting the correct query name
@@ -146,28 +313,34 @@ impl DnsHandlers {
         for query in request.queries() {
             let query_type = query.query_type();
 
-            // Only process TXT and A record types
-            if query_type != RecordType::TXT && query_type != RecordType::A {
+            // Only process record types services can answer
+            if !SERVICE_RECORD_TYPES.contains(&query_type) {
                 continue;
             }
 
-            let query_name = query.name();
-
-            // Build domain suffix and clean the query
-            let domain_suffix = format!(".{}.{}", suffix, self.domain.to_string());
-            let cleaned_query = Self::clean_query(&query_name.to_string(), &domain_suffix);
+            // Route on the lowercased name, but answer with the exact case the client sent
+            // so resolvers using 0x20 randomisation accept the response.
+            let query_name = query.original().name();
+            let mut labels = self.relative_labels(query.name());
+            if labels.pop().as_deref() != Some(suffix) {
+                continue;
+            }
+            let cleaned_query = DnsHandlers::clean_query(&labels.join("."));
 
             // Lookup the registered service for the given suffix
             if let Some(service) = self.services.get(suffix) {
                 // Call the unified query method
-                if let Some(records) = service
+                if let Some(answer) = service
                     .query(request, query_name, query_type, &cleaned_query)
                     .await
                 {
-                    // Set the correct query name for each record
+                    // Set the correct query name for each record and the zone's error TTL
                     let mut named_records = Vec::new();
-                    for mut record in records {
-                        record.set_name(query_name.clone().into());
+                    for mut record in answer.records {
+                        record.set_name(query_name.clone());
+                        if answer.is_error {
+                            record.set_ttl(self.error_ttl);
+                        }
                         named_records.push(record);
                     }
                     output_records.extend(named_records);
@@ -177,88 +350,22 @@ impl DnsHandlers {
         Ok(output_records)
     }
 
-    /// Cleans a DNS query string by removing the domain suffix and sanitizing the input.
-    ///
-    /// This function extracts the meaningful portion of a DNS query by removing
-    /// the service suffix and domain, then sanitizes the remaining text to ensure
-    /// it only contains safe characters.
-    ///
-    /// This is a "query parser" - it takes a full DNS query like "mumbai.time.example.com"
-    /// and extracts just "mumbai" as the actual query for the service. It also ensures
-    /// the extracted query is safe by removing potentially dangerous characters.
-    ///
-    /// ## Arguments
-    /// * `query` - The full DNS query string (e.g., "mumbai.time.example.com.")
-    /// * `suffix` - The suffix to remove from the end of the query (e.g., ".time.example.com.")
-    ///
-    /// ## Returns
-    /// A cleaned version of the query string, containing only allowed characters.
-    ///
-    /// ## Example
-    /// ```
-    /// let cleaned = clean_query("mumbai.time.example.com.", ".time.example.com.");
-    /// assert_eq!(cleaned, "mumbai");
-    /// ```
-    pub fn clean_query(query: &str, suffix: &str) -> String {
-        let without_trailing_dot = query.trim_end_matches('.');
-        let trimmed = without_trailing_dot.trim_end_matches(suffix);
-        RE_CLEAN.replace_all(trimmed, "").to_string()
-    }
-
-    /// Converts a vector of answer strings into a vector of DNS TXT records.
-    ///
-    /// This function takes the raw response strings from a service and converts
-    /// them into proper DNS TXT records. Each string becomes a separate TXT record
-    /// with a standard TTL and the root name as the record name.
+    /// Returns the lowercased labels of a name that sit below the zone apex.
     ///
-    /// This is a "response formatter" - it bridges the gap between service logic
-    /// (which returns simple strings) and DNS protocol requirements (which need
-    /// properly formatted records). It ensures service responses are compatible
-    /// with DNS standards.
+    /// For ``MuMbAi.GeO.example.com`` in the zone ``example.com`` this returns
+    /// ``["mumbai", "geo"]``, the last label being the service suffix.
     ///
     /// ## Arguments
-    /// * `answers` - A vector of strings, each representing a TXT record's data
+    /// * `name` - The lowercased DNS name being queried, which must be inside this zone
     ///
     /// ## Returns
-    /// * `Ok(Vec<Record>)` - A vector of DNS TXT records if all answers are successfully converted
-    /// * `Err(anyhow::Error)` - If there is an error parsing the root name
-    ///
-    /// ## Example
-    /// ```
-    /// let records = make_response(vec!["Hello, world!".to_string()]).unwrap();
-    /// assert_eq!(records.len(), 1);
-    /// ```
-    pub fn create_response(answers: Vec<String>) -> Result<Vec<Record>> {
-        let mut records = Vec::with_capacity(answers.len());
-        for answer in answers {
-            // Each answer is wrapped as a TXT record with TTL 60 and root name.
-            let record = Record::from_rdata(
-                Name::from_str(".")?,
-                60,
-                RData::TXT(rdata::TXT::new(vec![answer])),
-            );
-            records.push(record);
-        }
-        Ok(records)
-    }
-    /// Creates an error response as a TXT record.
-    ///
-    /// This function creates a standardized error response that can be returned
-    /// to DNS clients when something goes wrong. It uses a short TTL (1 second)
-    /// to ensure errors don't get cached for long periods.
-    ///
-    /// ## Arguments
-    /// * `query_name` - The DNS name that was queried
-    /// * `error_msg` - The error message to include in the response
-    ///
-    /// ## Returns
-    /// A DNS TXT record containing the error message
-    pub fn create_error_response(query_name: &Name, error_msg: &str) -> Record {
-        Record::from_rdata(
-            query_name.clone(),
-            1,
-            RData::TXT(rdata::TXT::new(vec![format!("error: {}", error_msg)])),
-        )
+    /// The labels left of the zone apex, in query order
+    fn relative_labels(&self, name: &LowerName) -> Vec<String> {
+        let count = name.num_labels().saturating_sub(self.domain.num_labels());
+        name.iter()
+            .take(count as usize)
+            .map(|label| String::from_utf8_lossy(label).to_string())
+            .collect()
     }
 
     /// Handles unknown DNS queries with an error message.
@@ -272,10 +379,12 @@ impl DnsHandlers {
     /// ## Returns
     /// A DNS TXT record with error message and help instructions
     pub fn handle_default_query(&self, query_name: &Name) -> Record {
-        Self::create_error_response(
+        let mut record = DnsHandlers::create_error_record(
             query_name,
-            &format!("unknown query, try: dig help @{}", self.domain.to_string()),
-        )
+            &format!("unknown query, try: dig help @{}", self.domain),
+        );
+        record.set_ttl(self.error_ttl);
+        record
     }
 
     /// Handles DNS queries for help information.
@@ -300,51 +409,438 @@ impl DnsHandlers {
             .collect()
     }
 
-    /// Processes DNS queries by routing them to appropriate services.
+    /// Processes a DNS query that belongs to this zone.
     ///
-    /// This is the business logic entry point for all DNS queries. It determines which service
-    /// to use based on the query name and delegates to the appropriate handler.
+    /// Answers SOA queries at the apex, help queries, and routes everything else to the
+    /// service registered for the matching suffix.
     ///
     /// ## Arguments
     /// * `request` - The incoming DNS request
     ///
     /// ## Returns
-    /// * `Ok(Vec<Record>)` - Vector of DNS records to return to the client
+    /// * `Ok(DnsResponse)` - DNS records to return to the client; empty answers carry the
+    ///   zone's SOA in the authority section for negative caching
     /// * `Err(anyhow::Error)` - If request processing fails
-    pub async fn process_dns_query(&self, request: &Request) -> Result<Vec<Record>> {
+    pub async fn process_query(&self, request: &Request) -> Result<DnsResponse> {
+        let query = &request.queries()[0];
+        let query_name = query.original().name();
+        let labels = self.relative_labels(query.name());
+
+        let answers = match labels.last().map(String::as_str) {
+            // Handle SOA queries at the zone apex
+            None if query.query_type() == RecordType::SOA => {
+                let mut soa = self.soa.clone();
+                soa.set_name(query_name.clone());
+                vec![soa]
+            }
+            // Handle help queries
+            Some("help") => self.handle_help_query(query_name),
+            // Handle service queries (ip, uuid, time, etc.)
+            Some(suffix) if self.services.contains_key(suffix) => {
+                self.process_service_request(request, suffix).await?
+            }
+            // Handle unknown queries using default query case
+            _ => vec![self.handle_default_query(query_name)],
+        };
+
+        let mut response = if answers.is_empty() {
+            DnsResponse {
+                answers,
+                authority: vec![self.negative_soa.clone()],
+                ..Default::default()
+            }
+        } else {
+            DnsResponse::answers(answers)
+        };
+
+        // Echo the client subnet with the scope the answering service depends on
+        response.client_subnet = DnsHandlers::client_subnet(request).map(|mut subnet| {
+            let scope = labels
+                .last()
+                .and_then(|suffix| self.services.get(suffix))
+                .map_or(0, |service| {
+                    service.client_subnet_scope(query.query_type(), &subnet)
+                });
+            subnet.set_scope_prefix(scope);
+            subnet
+        });
+        Ok(response)
+    }
+}
+
+/// Registry of authoritative zones served by this instance.
+///
+/// Incoming queries are routed to the zone whose apex is the longest suffix of the
+/// query name, so nested zones (e.g. `toys.example.com` inside `example.com`) work as expected.
+pub struct DnsHandlers {
+    pub zones: Vec<Zone>, // All zones this server is authoritative for.
+}
+
+impl DnsHandlers {
+    /// Creates an empty DnsHandlers instance.
+    ///
+    /// Zones are added with [`DnsHandlers::add_zone`] once their services and
+    /// help records have been set up.
+    pub fn new() -> Self {
+        DnsHandlers { zones: Vec::new() }
+    }
+
+    /// Adds an authoritative zone to the registry.
+    ///
+    /// ## Arguments
+    /// * `zone` - A fully populated zone
+    pub fn add_zone(&mut self, zone: Zone) {
+        self.zones.push(zone);
+    }
+
+    /// Finds the zone responsible for the given query name.
+    ///
+    /// ## Arguments
+    /// * `name` - The DNS name being queried
+    ///
+    /// ## Returns
+    /// * `Some(&Zone)` - The most specific zone containing the name
+    /// * `None` - If the name is outside of every configured zone
+    pub fn find_zone(&self, name: &LowerName) -> Option<&Zone> {
+        self.zones
+            .iter()
+            .filter(|zone| zone.domain.zone_of(name))
+            .max_by_key(|zone| zone.domain.num_labels())
+    }
+
+    /// Cleans the query portion of a DNS name by sanitizing the input.
+    ///
+    /// The zone router strips the service suffix and domain label-by-label, so this
+    /// function only has to ensure the remaining text contains safe characters.
+    ///
+    /// This is a "query sanitizer" - it takes the service argument of a DNS query (e.g. "mumbai"
+    /// from "mumbai.time.example.com") and removes potentially dangerous characters.
+    ///
+    /// ## Arguments
+    /// * `query` - The query labels left of the service suffix, joined with dots (e.g., "mumbai")
+    ///
+    /// ## Returns
+    /// A cleaned version of the query string, containing only allowed characters.
+    ///
+    /// ## Example
+    /// ```ignore
+    /// let cleaned = clean_query("mumbai!");
+    /// assert_eq!(cleaned, "mumbai");
+    /// ```
+    pub fn clean_query(query: &str) -> String {
+        RE_CLEAN.replace_all(query, "").to_string()
+    }
+
+    /// Splits text into TXT character-strings of at most 255 bytes each.
+    ///
+    /// Splits only happen on UTF-8 character boundaries, so multi-byte characters are
+    /// never cut in half. Empty text yields a single empty character-string.
+    ///
+    /// ## Arguments
+    /// * `text` - The text to split
+    ///
+    /// ## Returns
+    /// The character-strings in order; clients concatenate them to recover the text
+    pub fn split_txt(text: &str) -> Vec<String> {
+        let mut chunks = Vec::new();
+        let mut rest = text;
+        while rest.len() > MAX_TXT_STRING_LEN {
+            let mut end = MAX_TXT_STRING_LEN;
+            while !rest.is_char_boundary(end) {
+                end -= 1;
+            }
+            let (chunk, tail) = rest.split_at(end);
+            chunks.push(chunk.to_string());
+            rest = tail;
+        }
+        chunks.push(rest.to_string());
+        chunks
+    }
+
+    /// Creates TXT rdata for arbitrary text, splitting it into 255-byte character-strings.
+    ///
+    /// All services should use this instead of ``rdata::TXT::new(vec![text])``, which
+    /// produces malformed records once the text is longer than 255 bytes.
+    ///
+    /// ## Arguments
+    /// * `text` - The text to wrap
+    ///
+    /// ## Returns
+    /// TXT rdata holding the text as one or more character-strings
+    pub fn create_txt_rdata(text: &str) -> RData {
+        RData::TXT(rdata::TXT::new(Self::split_txt(text)))
+    }
+
+    /// Returns the EDNS Client Subnet option (RFC 7871) of a request, if any.
+    ///
+    /// Resolvers forwarding queries on behalf of a client may add the client's subnet,
+    /// since the request source is then the resolver rather than the client.
+    ///
+    /// ## Arguments
+    /// * `request` - The incoming DNS request
+    ///
+    /// ## Returns
+    /// * `Some(ClientSubnet)` - The subnet, with its source and scope prefix lengths
+    /// * `None` - If the request has no EDNS or no client subnet option
+    pub fn client_subnet(request: &Request) -> Option<ClientSubnet> {
+        match request.edns()?.option(EdnsCode::Subnet)? {
+            EdnsOption::Subnet(subnet) => Some(*subnet),
+            _ => None,
+        }
+    }
+
+    /// Creates an error response as a TXT record.
+    ///
+    /// This function creates a standardized error response that can be returned
+    /// to DNS clients when something goes wrong. The answer is flagged as an error, so
+    /// zones give it their configured `error_ttl` to ensure errors don't get cached for
+    /// long periods.
+    ///
+    /// ## Arguments
+    /// * `query_name` - The DNS name that was queried
+    /// * `error_msg` - The error message to include in the response
+    ///
+    /// ## Returns
+    /// An error answer holding a single TXT record with the error message
+    pub fn create_error_response(query_name: &Name, error_msg: &str) -> Answer {
+        Answer {
+            records: vec![Self::create_error_record(query_name, error_msg)],
+            is_error: true,
+        }
+    }
+
+    /// Creates the TXT record of an error response, with the short [`ERROR_TTL`].
+    ///
+    /// ## Arguments
+    /// * `query_name` - The DNS name that was queried
+    /// * `error_msg` - The error message to include in the record
+    ///
+    /// ## Returns
+    /// A DNS TXT record containing the error message
+    pub fn create_error_record(query_name: &Name, error_msg: &str) -> Record {
+        Record::from_rdata(
+            query_name.clone(),
+            ERROR_TTL,
+            Self::create_txt_rdata(&format!("error: {}", error_msg)),
+        )
+    }
+
+    /// Processes DNS queries by routing them to the appropriate zone and service.
+    ///
+    /// This is the business logic entry point for all DNS queries. It determines which zone
+    /// the query belongs to based on its name and delegates to that zone.
+    ///
+    /// ## Arguments
+    /// * `request` - The incoming DNS request
+    ///
+    /// ## Returns
+    /// * `Ok(Some(DnsResponse))` - DNS records to return to the client
+    /// * `Ok(None)` - If the query is outside every zone, so it must be refused
+    /// * `Err(anyhow::Error)` - If processing fails
+    pub async fn process_dns_query(&self, request: &Request) -> Result<Option<DnsResponse>> {
         if request.queries().is_empty() {
             return Err(anyhow!("No queries in request"));
         }
 
-        let query = &request.queries()[0];
-        let query_name = query.name();
-        let query_str = query_name.to_string();
+        let query_name = request.queries()[0].name();
+
+        let Some(zone) = self.find_zone(query_name) else {
+            tracing::info!(
+                "Refusing query {} outside of all configured zones",
+                query_name
+            );
+            return Ok(None);
+        };
 
         tracing::info!(
-            "Handling request - query_str: '{}', domain: '{}'",
-            query_str,
-            self.domain.to_string()
+            "Handling request - query_str: '{}', zone: '{}'",
+            query_name,
+            zone.domain
         );
 
-        // Handle help queries
-        if query_str.ends_with(&format!("help.{}.", self.domain.to_string())) {
-            return Ok(self.handle_help_query(query_name));
+        zone.process_query(request).await.map(Some)
+    }
+}
+
+impl Default for DnsHandlers {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Builds the answer records of a service response.
+///
+/// Services describe *what* to answer (text, addresses, locations) and the builder takes
+/// care of the DNS details: every record gets the query name and the service's TTL for its
+/// record type, and TXT text is split into 255-byte character-strings automatically.
+///
+/// ## Example
+/// ```ignore
+/// let mut response = ResponseBuilder::new(query_name, &self.ttls);
+/// response.txt("3.14159").a(Ipv4Addr::new(3, 141, 59, 27));
+/// response.build()
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseBuilder {
+    name: Name,
+    ttls: Ttls,
+    records: Vec<Record>,
+}
+
+impl ResponseBuilder {
+    /// Creates an empty response for the given query name.
+    ///
+    /// ## Arguments
+    /// * `name` - The DNS name being queried, used as owner name of every record
+    /// * `ttls` - The service's TTLs, picked per record type
+    pub fn new(name: &Name, ttls: &Ttls) -> Self {
+        Self {
+            name: name.clone(),
+            ttls: ttls.clone(),
+            records: Vec::new(),
         }
+    }
 
-        // Handle service queries (ip, uuid, time, etc.)
-        for (suffix, _) in &self.services {
-            // Check for both formats: "suffix.domain." and ".suffix.domain."
-            let expected_with_dot = format!(".{}.{}.", suffix, self.domain.to_string());
-            let expected_without_dot = format!("{}.{}.", suffix, self.domain.to_string());
+    /// Adds a record with arbitrary rdata to the response.
+    pub fn rdata(&mut self, rdata: RData) -> &mut Self {
+        let ttl = self.ttls.get(rdata.record_type());
+        self.records
+            .push(Record::from_rdata(self.name.clone(), ttl, rdata));
+        self
+    }
 
-            if query_str.ends_with(&expected_with_dot) || query_str.ends_with(&expected_without_dot)
-            {
-                return self.process_service_request(request, suffix).await;
-            }
+    /// Adds a TXT record, splitting long text into 255-byte character-strings.
+    pub fn txt(&mut self, text: &str) -> &mut Self {
+        self.rdata(DnsHandlers::create_txt_rdata(text))
+    }
+
+    /// Adds one or more TXT records for long text.
+    ///
+    /// The text is split into 255-byte character-strings, spread over several records
+    /// holding at most `max_strings_per_record` strings each. Note that resolvers may
+    /// reorder records within an RRset, so this should only be used for text whose parts
+    /// make sense on their own.
+    ///
+    /// ## Arguments
+    /// * `text` - The text to add
+    /// * `max_strings_per_record` - Character-strings per record, or 0 for a single record
+    pub fn txt_split(&mut self, text: &str, max_strings_per_record: usize) -> &mut Self {
+        let strings = DnsHandlers::split_txt(text);
+        let per_record = if max_strings_per_record == 0 {
+            strings.len()
+        } else {
+            max_strings_per_record
+        };
+
+        for chunk in strings.chunks(per_record) {
+            self.rdata(RData::TXT(rdata::TXT::new(chunk.to_vec())));
         }
+        self
+    }
+
+    /// Adds an A record.
+    pub fn a(&mut self, ip: Ipv4Addr) -> &mut Self {
+        self.rdata(RData::A(ip.into()))
+    }
 
-        // Handle unknown queries using default query case
-        Ok(vec![self.handle_default_query(query_name)])
+    /// Adds an AAAA record.
+    pub fn aaaa(&mut self, ip: Ipv6Addr) -> &mut Self {
+        self.rdata(RData::AAAA(ip.into()))
+    }
+
+    /// Adds a LOC record (RFC 1876) for a point on the WGS84 ellipsoid.
+    ///
+    /// ## Arguments
+    /// * `latitude` - Latitude in decimal degrees, positive north
+    /// * `longitude` - Longitude in decimal degrees, positive east
+    /// * `altitude` - Altitude in meters above the WGS84 reference spheroid
+    pub fn loc(&mut self, latitude: f64, longitude: f64, altitude: f64) -> &mut Self {
+        self.rdata(create_loc_rdata(latitude, longitude, altitude))
+    }
+
+    /// Adds a URI record (RFC 7553).
+    ///
+    /// ## Arguments
+    /// * `priority` - Priority of the target, lower values are preferred
+    /// * `weight` - Relative weight among targets with the same priority
+    /// * `target` - The target URI
+    pub fn uri(&mut self, priority: u16, weight: u16, target: &str) -> &mut Self {
+        let mut data = Vec::with_capacity(4 + target.len());
+        data.extend_from_slice(&priority.to_be_bytes());
+        data.extend_from_slice(&weight.to_be_bytes());
+        data.extend_from_slice(target.as_bytes());
+        self.rdata(RData::Unknown {
+            code: RecordType::Unknown(URI_RECORD_TYPE),
+            rdata: rdata::NULL::with(data),
+        })
+    }
+
+    /// Returns true if no records have been added yet.
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Returns the records added so far.
+    pub fn into_records(self) -> Vec<Record> {
+        self.records
+    }
+
+    /// Finishes the response in the shape expected by [`Service::query`].
+    ///
+    /// ## Returns
+    /// * `Some(Answer)` - The records, if any were added
+    /// * `None` - If the response is empty
+    pub fn build(self) -> Option<Answer> {
+        if self.records.is_empty() {
+            None
+        } else {
+            Some(self.records.into())
+        }
+    }
+}
+
+/// Encodes a size or precision in meters into the RFC 1876 mantissa/exponent byte.
+fn encode_loc_precision(meters: f64) -> u8 {
+    let mut value = (meters * 100.0).round().max(0.0);
+    let mut exponent = 0u8;
+    while value >= 10.0 && exponent < 9 {
+        value /= 10.0;
+        exponent += 1;
+    }
+    ((value.round().min(9.0) as u8) << 4) | exponent
+}
+
+/// Creates LOC rdata (RFC 1876) for a point, using the RFC's default size and precision.
+///
+/// Hickory does not implement LOC natively, so the rdata is encoded by hand and sent as
+/// an unknown record type.
+///
+/// ## Arguments
+/// * `latitude` - Latitude in decimal degrees, positive north
+/// * `longitude` - Longitude in decimal degrees, positive east
+/// * `altitude` - Altitude in meters above the WGS84 reference spheroid
+///
+/// ## Returns
+/// LOC rdata ready to be placed in a record
+pub fn create_loc_rdata(latitude: f64, longitude: f64, altitude: f64) -> RData {
+    // Angles are thousandths of an arc second offset by 2^31, altitude is
+    // centimeters above a base 100,000 m below the reference spheroid.
+    let encode_angle =
+        |degrees: f64| ((1u64 << 31) as f64 + (degrees * 3_600_000.0).round()) as u32;
+    let altitude = (altitude * 100.0 + 10_000_000.0).round().max(0.0) as u32;
+
+    let mut data = Vec::with_capacity(16);
+    data.push(0); // VERSION
+    data.push(encode_loc_precision(LOC_DEFAULT_SIZE));
+    data.push(encode_loc_precision(LOC_DEFAULT_HORIZ_PRE));
+    data.push(encode_loc_precision(LOC_DEFAULT_VERT_PRE));
+    data.extend_from_slice(&encode_angle(latitude.clamp(-90.0, 90.0)).to_be_bytes());
+    data.extend_from_slice(&encode_angle(longitude.clamp(-180.0, 180.0)).to_be_bytes());
+    data.extend_from_slice(&altitude.to_be_bytes());
+
+    RData::Unknown {
+        code: RecordType::Unknown(LOC_RECORD_TYPE),
+        rdata: rdata::NULL::with(data),
     }
 }
 
@@ -361,16 +857,67 @@ impl RdnsRequestHandler {
 
     /// Creates a response header with minimal configuration.
     /// Uses the built-in response_from_request which already handles most fields
-    fn create_response_header(&self, request: &Request, is_error: bool) -> Header {
+    fn create_response_header(&self, request: &Request, response_code: ResponseCode) -> Header {
         let mut header = Header::response_from_request(request.header());
-        header.set_authoritative(true);
+        // Only answers from one of our zones are authoritative
+        header.set_authoritative(response_code != ResponseCode::Refused);
         header.set_recursion_available(false);
+        header.set_response_code(response_code);
+        header
+    }
 
-        if is_error {
-            header.set_response_code(hickory_proto::op::ResponseCode::ServFail);
+    /// Sends a response without records, for refused and failed queries.
+    ///
+    /// No answer depends on the client subnet, so it is echoed with scope 0.
+    async fn send_no_records<R: ResponseHandler>(
+        &self,
+        request: &Request,
+        mut response_handle: R,
+        response_code: ResponseCode,
+    ) -> ResponseInfo {
+        let response_header = self.create_response_header(request, response_code);
+
+        let mut builder = MessageResponseBuilder::from_message_request(request);
+        let client_subnet = DnsHandlers::client_subnet(request).map(|mut subnet| {
+            subnet.set_scope_prefix(0);
+            subnet
+        });
+        if let Some(edns) = self.create_response_edns(request, client_subnet) {
+            builder.edns(edns);
         }
+        let response = builder.build_no_records(response_header);
 
-        header
+        response_handle
+            .send_response(response)
+            .await
+            .unwrap_or_else(|_err| {
+                tracing::error!("Failed to send {} response", response_code);
+                ResponseInfo::from(Header::new())
+            })
+    }
+
+    /// Creates the EDNS record of a response when the request used EDNS (RFC 6891),
+    /// advertising [`EDNS_PAYLOAD`].
+    ///
+    /// ## Arguments
+    /// * `request` - The incoming DNS request
+    /// * `client_subnet` - The client subnet to echo, with its scope already set
+    ///
+    /// ## Returns
+    /// * `Some(Edns)` - The response EDNS, if the request used EDNS
+    /// * `None` - If the request has no EDNS, so neither may the response
+    fn create_response_edns(
+        &self,
+        request: &Request,
+        client_subnet: Option<ClientSubnet>,
+    ) -> Option<Edns> {
+        request.edns()?;
+        let mut edns = Edns::new();
+        edns.set_version(EDNS_VERSION).set_max_payload(EDNS_PAYLOAD);
+        if let Some(subnet) = client_subnet {
+            edns.options_mut().insert(EdnsOption::Subnet(subnet));
+        }
+        Some(edns)
     }
 }
 
@@ -388,16 +935,20 @@ impl RequestHandler for RdnsRequestHandler {
     ) -> ResponseInfo {
         // Process the request using our custom handlers
         match self.handlers.process_dns_query(request).await {
-            Ok(records) => {
+            Ok(Some(dns_response)) => {
                 // Create response header using built-in function (much simpler!)
-                let response_header = self.create_response_header(request, false);
+                let response_header = self.create_response_header(request, ResponseCode::NoError);
 
-                // Create a MessageResponse with the records
-                let response = MessageResponseBuilder::from_message_request(request).build(
+                // Create a MessageResponse with the answer and authority records
+                let mut builder = MessageResponseBuilder::from_message_request(request);
+                if let Some(edns) = self.create_response_edns(request, dns_response.client_subnet) {
+                    builder.edns(edns);
+                }
+                let response = builder.build(
                     response_header,
-                    records.iter(),
-                    iter::empty(),
+                    dns_response.answers.iter(),
                     iter::empty(),
+                    dns_response.authority.iter(),
                     iter::empty(),
                 );
 
@@ -410,25 +961,416 @@ impl RequestHandler for RdnsRequestHandler {
                         ResponseInfo::from(Header::new())
                     })
             }
+            // Not our zone: refuse instead of failing, so resolvers don't mark us as broken
+            Ok(None) => {
+                self.send_no_records(request, response_handle, ResponseCode::Refused)
+                    .await
+            }
             Err(err) => {
                 tracing::error!("Error handling request: {}", err);
+                self.send_no_records(request, response_handle, ResponseCode::ServFail)
+                    .await
+            }
+        }
+    }
+}
 
-                // Create error response header using built-in function
-                let response_header = self.create_response_header(request, true);
+/// Builds a request for a single query, as if it arrived over UDP from `127.0.0.1:53`.
+///
+/// ## Arguments
+/// * `name` - The queried name
+/// * `query_type` - The queried record type
+/// * `edns` - The EDNS record of the request, if any
+#[cfg(test)]
+pub(crate) fn test_request(name: &Name, query_type: RecordType, edns: Option<Edns>) -> Request {
+    test_request_from("127.0.0.1:53".parse().unwrap(), name, query_type, edns)
+}
 
-                // Create an error MessageResponse with no records
-                let response = MessageResponseBuilder::from_message_request(request)
-                    .build_no_records(response_header);
+/// Builds a request like [`test_request`], from the given client address.
+#[cfg(test)]
+pub(crate) fn test_request_from(
+    src: std::net::SocketAddr,
+    name: &Name,
+    query_type: RecordType,
+    edns: Option<Edns>,
+) -> Request {
+    use hickory_proto::op::{Message, Query};
+    use hickory_proto::serialize::binary::BinDecodable;
+    use hickory_proto::xfer::Protocol;
+    use hickory_server::authority::MessageRequest;
 
-                // Send the error response
-                response_handle
-                    .send_response(response)
-                    .await
-                    .unwrap_or_else(|_err| {
-                        tracing::error!("Failed to send error response");
-                        ResponseInfo::from(hickory_proto::op::Header::new())
-                    })
+    let mut message = Message::new();
+    message.add_query(Query::query(name.clone(), query_type));
+    if let Some(edns) = edns {
+        message.set_edns(edns);
+    }
+    let bytes = message.to_vec().unwrap();
+    let message = MessageRequest::from_bytes(&bytes).unwrap();
+    Request::new(message, src, Protocol::Udp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hickory_proto::serialize::binary::BinEncodable;
+
+    /// Answers every query with the cleaned query it received, or an error for `fail`.
+    struct EchoService;
+
+    #[async_trait]
+    impl Service for EchoService {
+        async fn query(
+            &self,
+            _request: &Request,
+            query_name: &Name,
+            _query_type: RecordType,
+            cleaned_query: &str,
+        ) -> Option<Answer> {
+            if cleaned_query == "fail" {
+                return Some(DnsHandlers::create_error_response(query_name, "failed"));
             }
+            let mut response = ResponseBuilder::new(query_name, &Ttls::new(60));
+            response.txt(cleaned_query);
+            response.build()
+        }
+
+        async fn dump(&self) -> Result<Vec<u8>> {
+            Ok(Vec::new())
+        }
+    }
+
+    fn zone(apex: &str) -> Zone {
+        let apex = Name::from_ascii(apex).unwrap();
+        let soa = rdata::SOA::new(apex.clone(), apex.clone(), 1, 3600, 600, 86400, 30);
+        let mut zone = Zone::new(LowerName::from(&apex), soa, 3600);
+        zone.register("geo".to_string(), Arc::new(EchoService));
+        zone
+    }
+
+    fn request(name: &str, query_type: RecordType) -> Request {
+        // from_str goes through IDNA, which lowercases the name
+        test_request(&Name::from_ascii(name).unwrap(), query_type, None)
+    }
+
+    fn edns_request(max_payload: u16) -> Request {
+        let mut edns = Edns::new();
+        edns.set_max_payload(max_payload);
+        test_request(
+            &Name::from_ascii("x.geo.localhost.").unwrap(),
+            RecordType::TXT,
+            Some(edns),
+        )
+    }
+
+    fn txt(record: &Record) -> String {
+        match record.data() {
+            RData::TXT(txt) => txt
+                .txt_data()
+                .iter()
+                .map(|part| String::from_utf8_lossy(part).to_string())
+                .collect(),
+            other => panic!("expected TXT, got {:?}", other),
         }
     }
+
+    fn name(name: &str) -> LowerName {
+        LowerName::from(Name::from_ascii(name).unwrap())
+    }
+
+    #[test]
+    fn find_zone_picks_the_longest_matching_apex() {
+        let mut handlers = DnsHandlers::new();
+        handlers.add_zone(zone("example.com"));
+        handlers.add_zone(zone("toys.example.com"));
+
+        let found = handlers.find_zone(&name("mumbai.geo.toys.example.com."));
+        assert_eq!(found.unwrap().domain, name("toys.example.com"));
+
+        let found = handlers.find_zone(&name("mumbai.geo.example.com."));
+        assert_eq!(found.unwrap().domain, name("example.com"));
+
+        assert!(handlers.find_zone(&name("example.org.")).is_none());
+    }
+
+    #[tokio::test]
+    async fn nested_zone_serves_its_own_services() {
+        let mut handlers = DnsHandlers::new();
+        handlers.add_zone(zone("example.com"));
+        handlers.add_zone(zone("toys.example.com"));
+
+        let records = handlers
+            .process_dns_query(&request("mumbai.geo.toys.example.com.", RecordType::TXT))
+            .await
+            .unwrap()
+            .expect("query inside the zone was refused");
+        assert_eq!(txt(&records.answers[0]), "mumbai");
+    }
+
+    #[tokio::test]
+    async fn queries_outside_every_zone_are_refused() {
+        let mut handlers = DnsHandlers::new();
+        handlers.add_zone(zone("example.com"));
+
+        let response = handlers
+            .process_dns_query(&request("mumbai.geo.example.org.", RecordType::TXT))
+            .await
+            .unwrap();
+        assert!(response.is_none());
+    }
+
+    #[test]
+    fn split_txt_keeps_short_text_whole() {
+        assert_eq!(DnsHandlers::split_txt("mumbai"), ["mumbai"]);
+        assert_eq!(DnsHandlers::split_txt(""), [""]);
+        assert_eq!(DnsHandlers::split_txt(&"a".repeat(255)).len(), 1);
+    }
+
+    #[test]
+    fn split_txt_cuts_long_text_into_255_byte_strings() {
+        let text = "a".repeat(600);
+        let chunks = DnsHandlers::split_txt(&text);
+        let lengths: Vec<_> = chunks.iter().map(String::len).collect();
+        assert_eq!(lengths, [255, 255, 90]);
+        assert_eq!(chunks.concat(), text);
+    }
+
+    #[test]
+    fn split_txt_never_cuts_a_character_in_half() {
+        // 2-byte characters put the 255th byte in the middle of a character
+        let text = "é".repeat(200);
+        let chunks = DnsHandlers::split_txt(&text);
+        assert_eq!(chunks[0].len(), 254);
+        assert!(chunks.iter().all(|chunk| chunk.len() <= MAX_TXT_STRING_LEN));
+        assert_eq!(chunks.concat(), text);
+    }
+
+    #[test]
+    fn txt_split_spreads_strings_over_records() {
+        let name = Name::from_ascii("x.geo.localhost.").unwrap();
+        let text = "a".repeat(600);
+
+        let mut response = ResponseBuilder::new(&name, &Ttls::new(60));
+        response.txt_split(&text, 1);
+        let records = response.into_records();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records.iter().map(txt).collect::<String>(), text);
+
+        let mut response = ResponseBuilder::new(&name, &Ttls::new(60));
+        response.txt_split(&text, 0);
+        let records = response.into_records();
+        assert_eq!(records.len(), 1);
+        match records[0].data() {
+            RData::TXT(rdata) => assert_eq!(rdata.txt_data().len(), 3),
+            other => panic!("expected TXT, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn mixed_case_name_routes_to_lowercase_suffix_and_query() {
+        let zone = zone("localhost.");
+        let request = request("MuMbAi.GeO.localhost.", RecordType::TXT);
+
+        let labels = zone.relative_labels(request.queries()[0].name());
+        assert_eq!(labels, ["mumbai", "geo"]);
+
+        let response = zone.process_query(&request).await.unwrap();
+        assert_eq!(response.answers.len(), 1);
+        assert_eq!(txt(&response.answers[0]), "mumbai");
+    }
+
+    #[tokio::test]
+    async fn answers_keep_the_exact_case_of_the_query_name() {
+        let zone = zone("localhost.");
+        let request = request("MuMbAi.GeO.localhost.", RecordType::TXT);
+
+        let response = zone.process_query(&request).await.unwrap();
+        // Name equality ignores case, so compare the presentation form
+        assert_eq!(
+            response.answers[0].name().to_string(),
+            "MuMbAi.GeO.localhost."
+        );
+    }
+
+    #[tokio::test]
+    async fn mixed_case_zone_apex_still_matches() {
+        let mut handlers = DnsHandlers::new();
+        handlers.add_zone(zone("LocalHost."));
+        let request = request("mumbai.geo.LOCALHOST.", RecordType::TXT);
+
+        let response = handlers.process_dns_query(&request).await.unwrap();
+        let response = response.expect("query inside the zone was refused");
+        assert_eq!(txt(&response.answers[0]), "mumbai");
+    }
+
+    #[tokio::test]
+    async fn error_records_use_the_zone_error_ttl() {
+        let mut zone = zone("localhost.");
+        zone.set_error_ttl(30);
+
+        let response = zone
+            .process_query(&request("fail.geo.localhost.", RecordType::TXT))
+            .await
+            .unwrap();
+        assert_eq!(txt(&response.answers[0]), "error: failed");
+        assert_eq!(response.answers[0].ttl(), 30);
+
+        let response = zone
+            .process_query(&request("mumbai.nothing.localhost.", RecordType::TXT))
+            .await
+            .unwrap();
+        assert!(txt(&response.answers[0]).starts_with("error: "));
+        assert_eq!(response.answers[0].ttl(), 30);
+
+        // Regular answers keep the service TTL
+        let response = zone
+            .process_query(&request("mumbai.geo.localhost.", RecordType::TXT))
+            .await
+            .unwrap();
+        assert_eq!(response.answers[0].ttl(), 60);
+    }
+
+    #[test]
+    fn ttls_override_single_record_types() {
+        let overrides = HashMap::from([("a".to_string(), 300), ("LOC".to_string(), 86400)]);
+        let ttls = Ttls::from_config(60, &overrides).unwrap();
+
+        assert_eq!(ttls.get(RecordType::A), 300);
+        assert_eq!(ttls.get(RecordType::Unknown(LOC_RECORD_TYPE)), 86400);
+        assert_eq!(ttls.get(RecordType::TXT), 60);
+        assert_eq!(ttls.get(RecordType::AAAA), 60);
+    }
+
+    #[test]
+    fn ttls_reject_unknown_record_types() {
+        let overrides = HashMap::from([("TXTT".to_string(), 300)]);
+        let error = Ttls::from_config(60, &overrides).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Unknown record type 'TXTT' in TTL overrides"
+        );
+    }
+
+    #[tokio::test]
+    async fn empty_answers_carry_the_soa_with_the_negative_ttl() {
+        // SOA minimum 30 is below the SOA TTL of 3600
+        let zone = zone("localhost.");
+        let response = zone
+            .process_query(&request("mumbai.geo.localhost.", RecordType::MX))
+            .await
+            .unwrap();
+        assert!(response.answers.is_empty());
+        assert_eq!(response.authority.len(), 1);
+        assert_eq!(response.authority[0].record_type(), RecordType::SOA);
+        assert_eq!(response.authority[0].ttl(), 30);
+
+        // An SOA TTL below the minimum caps the negative TTL
+        let apex = Name::from_ascii("localhost.").unwrap();
+        let soa = rdata::SOA::new(apex.clone(), apex.clone(), 1, 3600, 600, 86400, 30);
+        let mut zone = Zone::new(LowerName::from(&apex), soa, 10);
+        zone.register("geo".to_string(), Arc::new(EchoService));
+        let response = zone
+            .process_query(&request("mumbai.geo.localhost.", RecordType::MX))
+            .await
+            .unwrap();
+        assert_eq!(response.authority[0].ttl(), 10);
+
+        // Regular answers have no authority section
+        let response = zone
+            .process_query(&request("localhost.", RecordType::SOA))
+            .await
+            .unwrap();
+        assert!(response.authority.is_empty());
+        assert_eq!(response.answers[0].ttl(), 10);
+    }
+
+    fn loc_bytes(rdata: &RData) -> Vec<u8> {
+        match rdata {
+            RData::Unknown { code, rdata } => {
+                assert_eq!(*code, RecordType::Unknown(LOC_RECORD_TYPE));
+                rdata.anything().to_vec()
+            }
+            other => panic!("expected LOC, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn loc_precision_uses_mantissa_and_exponent_in_centimeters() {
+        assert_eq!(encode_loc_precision(LOC_DEFAULT_SIZE), 0x12);
+        assert_eq!(encode_loc_precision(LOC_DEFAULT_HORIZ_PRE), 0x16);
+        assert_eq!(encode_loc_precision(LOC_DEFAULT_VERT_PRE), 0x13);
+        assert_eq!(encode_loc_precision(0.0), 0x00);
+    }
+
+    #[test]
+    fn loc_rdata_encodes_the_origin() {
+        let bytes = loc_bytes(&create_loc_rdata(0.0, 0.0, 0.0));
+        assert_eq!(
+            bytes,
+            [
+                0x00, 0x12, 0x16, 0x13, // version, size, precisions
+                0x80, 0x00, 0x00, 0x00, // latitude 2^31
+                0x80, 0x00, 0x00, 0x00, // longitude 2^31
+                0x00, 0x98, 0x96, 0x80, // altitude 10,000,000 cm
+            ]
+        );
+    }
+
+    #[test]
+    fn loc_rdata_encodes_signed_angles_and_altitude() {
+        let bytes = loc_bytes(&create_loc_rdata(-33.5, 151.25, 58.0));
+        let field = |at: usize| u32::from_be_bytes(bytes[at..at + 4].try_into().unwrap());
+
+        assert_eq!(field(4), (1 << 31) - 120_600_000);
+        assert_eq!(field(8), (1 << 31) + 544_500_000);
+        assert_eq!(field(12), 10_005_800);
+
+        // Altitudes below the 100 km base clamp to it
+        let bytes = loc_bytes(&create_loc_rdata(0.0, 0.0, -200_000.0));
+        assert_eq!(&bytes[12..], [0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn uri_records_use_the_rfc_7553_wire_format() {
+        let name = Name::from_ascii("x.geo.localhost.").unwrap();
+        let mut response = ResponseBuilder::new(&name, &Ttls::new(60));
+        response.uri(10, 1, "https://example.com/");
+        let records = response.into_records();
+        assert_eq!(records.len(), 1);
+        assert_eq!(
+            records[0].record_type(),
+            RecordType::Unknown(URI_RECORD_TYPE)
+        );
+
+        let wire = records[0].to_bytes().unwrap();
+        let rdata = &wire[wire.len() - 24..];
+        let header = &wire[..wire.len() - 24];
+        // Type 256, class IN, TTL 60 and a 24 byte rdata length end the header
+        assert_eq!(
+            header[header.len() - 10..],
+            [0x01, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x3c, 0x00, 0x18]
+        );
+        assert_eq!(&rdata[..4], [0x00, 0x0a, 0x00, 0x01]); // priority, weight
+        assert_eq!(&rdata[4..], b"https://example.com/");
+
+        assert_eq!(
+            parse_record_type("uri"),
+            Some(RecordType::Unknown(URI_RECORD_TYPE))
+        );
+    }
+
+    #[test]
+    fn edns_responses_advertise_the_server_payload() {
+        let handler = RdnsRequestHandler::new(DnsHandlers::new());
+
+        for offered in [512, 1232, 4096, 65535] {
+            let edns = handler
+                .create_response_edns(&edns_request(offered), None)
+                .unwrap();
+            assert_eq!(edns.max_payload(), EDNS_PAYLOAD, "{}", offered);
+            assert_eq!(edns.version(), EDNS_VERSION);
+        }
+
+        let plain = request("x.geo.localhost.", RecordType::TXT);
+        assert!(handler.create_response_edns(&plain, None).is_none());
+    }
 }
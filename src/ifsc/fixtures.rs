@@ -0,0 +1,108 @@
+//! Bank files shared by the tests of the IFSC index and the services built on it.
+
+use super::IFSC;
+use std::fs;
+use tempfile::TempDir;
+
+/// A bank file with a single branch.
+pub(crate) const GOOD_BANK: &str = r#"{
+    "HDFC0000001": {
+        "BANK": "HDFC Bank",
+        "IFSC": "HDFC0000001",
+        "MICR": "400240015",
+        "BRANCH": "SANDOZ HOUSE",
+        "ADDRESS": "SANDOZ HOUSE, WORLI, MUMBAI 400018",
+        "STATE": "MAHARASHTRA",
+        "CITY": "MUMBAI",
+        "CENTRE": "MUMBAI",
+        "DISTRICT": "MUMBAI"
+    }
+}"#;
+
+/// A bank file with branches sharing a MICR code, a district and a city.
+pub(crate) const SBIN_BANK: &str = r#"{
+    "SBIN0000454": {
+        "BANK": "State Bank of India",
+        "IFSC": "SBIN0000454",
+        "MICR": "411002002",
+        "BRANCH": "PUNE MAIN",
+        "ADDRESS": "LAXMI ROAD, PUNE 411002",
+        "STATE": "MAHARASHTRA",
+        "CITY": "PUNE",
+        "DISTRICT": "PUNE"
+    },
+    "SBIN0001110": {
+        "BANK": "State Bank of India",
+        "IFSC": "SBIN0001110",
+        "MICR": "411002003",
+        "BRANCH": "SHIVAJINAGAR",
+        "ADDRESS": "SHIVAJINAGAR, PUNE 411005",
+        "STATE": "MAHARASHTRA",
+        "CITY": "PUNE",
+        "DISTRICT": "PUNE"
+    },
+    "SBIN0002222": {
+        "BANK": "State Bank of India",
+        "IFSC": "SBIN0002222",
+        "MICR": "411002003",
+        "BRANCH": "PIMPRI",
+        "ADDRESS": "PIMPRI, PUNE 411018",
+        "STATE": "MAHARASHTRA",
+        "CITY": "PIMPRI CHINCHWAD",
+        "DISTRICT": "PUNE"
+    },
+    "SBIN0003333": {
+        "BANK": "State Bank of India",
+        "IFSC": "SBIN0003333",
+        "MICR": null,
+        "BRANCH": "VASHI",
+        "ADDRESS": "SECTOR 17, VASHI, NAVI MUMBAI 400703",
+        "STATE": "MAHARASHTRA",
+        "CITY": "NAVI MUMBAI",
+        "DISTRICT": "THANE"
+    }
+}"#;
+
+/// A bank file with full, partial and missing payment rail fields.
+pub(crate) const KKBK_BANK: &str = r#"{
+    "KKBK0000001": {
+        "BANK": "Kotak Mahindra Bank",
+        "IFSC": "KKBK0000001",
+        "BRANCH": "NARIMAN POINT",
+        "CITY": "MUMBAI",
+        "DISTRICT": "MUMBAI",
+        "UPI": true,
+        "IMPS": true,
+        "NEFT": true,
+        "RTGS": true,
+        "SWIFT": "KKBKINBBXXX",
+        "CONTACT": "+912266056825"
+    },
+    "KKBK0000002": {
+        "BANK": "Kotak Mahindra Bank",
+        "IFSC": "KKBK0000002",
+        "BRANCH": "ANDHERI",
+        "CITY": "MUMBAI",
+        "DISTRICT": "MUMBAI",
+        "UPI": false,
+        "IMPS": null,
+        "NEFT": true,
+        "SWIFT": null
+    },
+    "KKBK0000003": {
+        "BANK": "Kotak Mahindra Bank",
+        "IFSC": "KKBK0000003",
+        "BRANCH": "BANDRA",
+        "CITY": "MUMBAI",
+        "DISTRICT": "MUMBAI"
+    }
+}"#;
+
+/// Loads bank files, given as (name, contents), from a temporary directory.
+pub(crate) fn load(banks: &[(&str, &str)]) -> IFSC {
+    let dir = TempDir::new().unwrap();
+    for (name, contents) in banks {
+        fs::write(dir.path().join(name), contents).unwrap();
+    }
+    IFSC::new(dir.path()).unwrap()
+}
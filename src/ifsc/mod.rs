@@ -4,59 +4,431 @@
 //! fast lookups for Indian bank branch information by IFSC code.
 //! It's a direct port of the Go version's `ifsc` package.
 
-use anyhow::{Context, Ok, Result};
-use serde::{Deserialize, Serialize};
+#[cfg(test)]
+pub(crate) mod fixtures;
+
+use anyhow::{Context, Result, bail};
+use flate2::read::GzDecoder;
+use serde::de::{IgnoredAny, MapAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json;
 use std::collections::HashMap;
-use std::fs;
-use std::path::Path;
+use std::fmt;
+use std::fs::{self, File};
+use std::io::{BufReader, Read};
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
 
 const IFSC_CODE_LEN: usize = 11;
 
+/// Length of the bank code at the start of an IFSC code
+const BANK_CODE_LEN: usize = 4;
+
+/// Length of a MICR code (3 digits city, 3 digits bank, 3 digits branch)
+const MICR_CODE_LEN: usize = 9;
+
+/// Payment rails a branch can support, in answer order
+pub const PAYMENT_RAILS: [&str; 5] = ["UPI", "IMPS", "NEFT", "RTGS", "SWIFT"];
+
+/// Lowercases a city or district name and strips everything but letters and digits
+/// (e.g., "NAVI MUMBAI" becomes "navimumbai", matching the `navi-mumbai` DNS label).
+fn clean_place(text: &str) -> String {
+    text.chars()
+        .filter(char::is_ascii_alphanumeric)
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/// Checks that a code is a MICR code, 9 digits printed on cheques.
+pub fn is_micr(code: &str) -> bool {
+    code.len() == MICR_CODE_LEN && code.bytes().all(|b| b.is_ascii_digit())
+}
+
+/// Checks that a code is a bank code, the 4 letters starting its IFSC codes.
+pub fn is_bank_code(code: &str) -> bool {
+    code.len() == BANK_CODE_LEN && code.bytes().all(|b| b.is_ascii_alphabetic())
+}
+
+/// Why an IFSC lookup did not return a branch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LookupError {
+    /// The code does not follow the IFSC format
+    Malformed {
+        /// The code as queried
+        code: String,
+        /// Which part of the format is violated
+        reason: &'static str,
+    },
+    /// The code is well-formed, but no loaded branch has it
+    Unknown {
+        /// The uppercased code
+        code: String,
+        /// Name of the bank owning the 4-letter prefix, if any of its branches are loaded
+        bank: Option<String>,
+    },
+}
+
+impl fmt::Display for LookupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Malformed { code, reason } => {
+                write!(f, "malformed IFSC code '{}': {}", code, reason)
+            }
+            Self::Unknown {
+                code,
+                bank: Some(bank),
+            } => write!(f, "unknown IFSC code {} (bank: {})", code, bank),
+            Self::Unknown { code, bank: None } => write!(
+                f,
+                "unknown IFSC code {} (unknown bank {})",
+                code,
+                code.get(..BANK_CODE_LEN).unwrap_or(code)
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LookupError {}
+
+/// Checks that a code follows the IFSC format: 4 letters (the bank), a literal `0`
+/// and 6 letters or digits (the branch).
+///
+/// # Arguments
+///
+/// * `code` - The code to check (case-insensitive)
+///
+/// # Returns
+///
+/// * `Ok(String)` - The uppercased code
+/// * `Err(LookupError::Malformed)` - If the code does not follow the format
+pub fn validate(code: &str) -> Result<String, LookupError> {
+    let malformed = |reason| LookupError::Malformed {
+        code: code.to_string(),
+        reason,
+    };
+
+    let bytes = code.as_bytes();
+    if bytes.len() != IFSC_CODE_LEN {
+        return Err(malformed("must be 11 characters long"));
+    }
+    if !bytes[..BANK_CODE_LEN].iter().all(u8::is_ascii_alphabetic) {
+        return Err(malformed(
+            "the first 4 characters (bank code) must be letters",
+        ));
+    }
+    if bytes[BANK_CODE_LEN] != b'0' {
+        return Err(malformed("the 5th character must be 0"));
+    }
+    if !bytes[BANK_CODE_LEN + 1..]
+        .iter()
+        .all(u8::is_ascii_alphanumeric)
+    {
+        return Err(malformed(
+            "the last 6 characters (branch code) must be letters or digits",
+        ));
+    }
+
+    Ok(code.to_ascii_uppercase())
+}
+
+/// Deserializes a string that may be `null` in the dataset (e.g., a branch without a MICR
+/// code) as an empty string.
+fn null_as_empty<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    Ok(Option::<String>::deserialize(deserializer)?.unwrap_or_default())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Branch {
     #[serde(rename = "BANK")]
     pub bank: String,
     #[serde(rename = "IFSC")]
     pub ifsc: String,
-    #[serde(rename = "MICR")]
+    #[serde(rename = "MICR", default, deserialize_with = "null_as_empty")]
     pub micr: String,
-    #[serde(rename = "BRANCH")]
+    #[serde(rename = "BRANCH", default, deserialize_with = "null_as_empty")]
     pub branch: String,
-    #[serde(rename = "ADDRESS")]
+    #[serde(rename = "ADDRESS", default, deserialize_with = "null_as_empty")]
     pub address: String,
-    #[serde(rename = "STATE")]
+    #[serde(rename = "STATE", default, deserialize_with = "null_as_empty")]
     pub state: String,
-    #[serde(rename = "CITY")]
+    #[serde(rename = "CITY", default, deserialize_with = "null_as_empty")]
     pub city: String,
-    #[serde(rename = "CENTRE")]
+    #[serde(rename = "CENTRE", default, deserialize_with = "null_as_empty")]
     pub centre: String,
-    #[serde(rename = "DISTRICT")]
+    #[serde(rename = "DISTRICT", default, deserialize_with = "null_as_empty")]
     pub district: String,
+    /// Whether the branch accepts UPI payments, if known
+    #[serde(rename = "UPI", default)]
+    pub upi: Option<bool>,
+    /// Whether the branch accepts RTGS transfers, if known
+    #[serde(rename = "RTGS", default)]
+    pub rtgs: Option<bool>,
+    /// Whether the branch accepts NEFT transfers, if known
+    #[serde(rename = "NEFT", default)]
+    pub neft: Option<bool>,
+    /// Whether the branch accepts IMPS transfers, if known
+    #[serde(rename = "IMPS", default)]
+    pub imps: Option<bool>,
+    /// SWIFT code of the branch, for international transfers
+    #[serde(rename = "SWIFT", default, deserialize_with = "null_as_empty")]
+    pub swift: String,
+    /// Phone number of the branch
+    #[serde(rename = "CONTACT", default, deserialize_with = "null_as_empty")]
+    pub contact: String,
+}
+
+impl Branch {
+    /// Returns which of the [`PAYMENT_RAILS`] the branch supports; SWIFT counts as
+    /// supported when the branch has a SWIFT code.
+    ///
+    /// # Returns
+    ///
+    /// * `Option<[bool; 5]>` - Support for each rail, or `None` if the dataset has no
+    ///   payment information for the branch
+    pub fn payment_rails(&self) -> Option<[bool; PAYMENT_RAILS.len()]> {
+        let flags = [self.upi, self.imps, self.neft, self.rtgs];
+        if flags.iter().all(Option::is_none) && self.swift.is_empty() {
+            return None;
+        }
+
+        let [upi, imps, neft, rtgs] = flags.map(|flag| flag == Some(true));
+        Some([upi, imps, neft, rtgs, !self.swift.is_empty()])
+    }
+}
+
+/// Summary of the loaded branches of one bank.
+#[derive(Debug, Clone, Default)]
+pub struct BankSummary {
+    /// The 4-letter bank code (e.g., "HDFC")
+    pub code: String,
+    /// Name of the bank
+    pub name: String,
+    /// Number of loaded branches
+    pub branches: usize,
+    /// Number of branches supporting each of the [`PAYMENT_RAILS`]
+    pub rails: [usize; PAYMENT_RAILS.len()],
+}
+
+impl BankSummary {
+    /// Counts a branch of the bank.
+    fn add(&mut self, branch: &Branch) {
+        self.branches += 1;
+        if let Some(rails) = branch.payment_rails() {
+            for (count, supported) in self.rails.iter_mut().zip(rails) {
+                *count += usize::from(supported);
+            }
+        }
+    }
+}
+
+/// The branches of a bank file, deserialized straight from the JSON object without
+/// keeping its IFSC code keys around.
+struct BankFile(Vec<Branch>);
+
+impl<'de> Deserialize<'de> for BankFile {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BankFileVisitor;
+
+        impl<'de> Visitor<'de> for BankFileVisitor {
+            type Value = BankFile;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a map of IFSC codes to branches")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<BankFile, A::Error> {
+                let mut branches = Vec::with_capacity(map.size_hint().unwrap_or(0));
+                while let Some((_, branch)) = map.next_entry::<IgnoredAny, Branch>()? {
+                    branches.push(branch);
+                }
+                Ok(BankFile(branches))
+            }
+        }
+
+        deserializer.deserialize_map(BankFileVisitor)
+    }
+}
+
+/// Parses the branches of one bank from a JSON reader, without buffering the whole file.
+///
+/// # Arguments
+///
+/// * `reader` - The JSON bank file (e.g., an open `SBIN.json`)
+///
+/// # Returns
+///
+/// * `Result<Vec<Branch>>` - The branches in file order, or an error if the JSON is invalid
+pub fn read_bank<R: Read>(reader: R) -> Result<Vec<Branch>> {
+    let bank: BankFile = serde_json::from_reader(BufReader::new(reader))?;
+    Ok(bank.0)
+}
+
+/// Checks whether a path has a `.json` extension (case-insensitive).
+fn is_json(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("json"))
+}
+
+/// Returns the bank code of a bank file, its uppercased file stem (e.g., "SBIN" for `SBIN.json`).
+fn bank_code(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().to_uppercase())
+        .unwrap_or_default()
+}
+
+/// Checks whether a path names a `.tar.gz`/`.tgz` archive (case-insensitive).
+fn is_tar_gz(path: &Path) -> bool {
+    let name = path.to_string_lossy().to_ascii_lowercase();
+    name.ends_with(".tar.gz") || name.ends_with(".tgz")
+}
+
+/// Checks whether a path names a `.zip` archive (case-insensitive).
+fn is_zip(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("zip"))
+}
+
+/// Load statistics of one bank file.
+#[derive(Debug, Clone)]
+pub struct BankStats {
+    /// Bank code, taken from the file name (e.g., "SBIN" for `SBIN.json`)
+    pub bank_code: String,
+    /// Where the file was read from
+    pub source: String,
+    /// Number of branches read from the file
+    pub branches: usize,
+    /// Time spent reading and parsing the file
+    pub elapsed: Duration,
+}
+
+/// Outcome of loading the IFSC data.
+#[derive(Debug, Clone, Default)]
+pub struct LoadReport {
+    /// Bank files that were loaded, in file name order for a directory and in archive
+    /// order for an archive
+    pub banks: Vec<BankStats>,
+    /// Bank files that could not be read or parsed, with the reason
+    pub skipped: Vec<(String, String)>,
+    /// Number of files ignored because they are not JSON (e.g., a README)
+    pub ignored: usize,
+    /// Total time of the load, including indexing
+    pub elapsed: Duration,
+}
+
+impl LoadReport {
+    /// Records the outcome of reading one bank file, adding its branches on success.
+    fn record(
+        &mut self,
+        branches: &mut Vec<Branch>,
+        path: &Path,
+        source: String,
+        result: Result<Vec<Branch>>,
+        elapsed: Duration,
+    ) {
+        match result {
+            Ok(bank) => {
+                self.banks.push(BankStats {
+                    bank_code: bank_code(path),
+                    source,
+                    branches: bank.len(),
+                    elapsed,
+                });
+                branches.extend(bank);
+            }
+            Err(e) => {
+                tracing::warn!("Skipping IFSC file {}: {:#}", source, e);
+                self.skipped.push((source, format!("{:#}", e)));
+            }
+        }
+    }
+}
+
+/// Reads and parses bank files on all available cores.
+///
+/// Every worker takes the next unread file until none are left, so large and small
+/// bank files balance out across threads.
+///
+/// # Returns
+///
+/// * `Vec<(Result<Vec<Branch>>, Duration)>` - The outcome of each file and the time
+///   spent on it, in the order of `paths`
+fn read_banks_parallel(paths: &[PathBuf]) -> Vec<(Result<Vec<Branch>>, Duration)> {
+    let next = AtomicUsize::new(0);
+    let workers = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(paths.len())
+        .max(1);
+
+    let mut results: Vec<_> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut done = Vec::new();
+                    while let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let started = Instant::now();
+                        let result = File::open(path)
+                            .map_err(anyhow::Error::from)
+                            .and_then(read_bank);
+                        done.push((path, result, started.elapsed()));
+                    }
+                    done
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("IFSC loader thread panicked"))
+            .collect()
+    });
+
+    results.sort_by(|a, b| a.0.cmp(b.0));
+    results
+        .into_iter()
+        .map(|(_, result, elapsed)| (result, elapsed))
+        .collect()
 }
 
 pub struct IFSC {
     data: HashMap<String, Branch>,
+    /// Bank summaries keyed by the 4-letter bank code of their IFSC codes
+    banks: HashMap<String, BankSummary>,
+    /// IFSC codes of the branches sharing each MICR code, sorted
+    by_micr: HashMap<String, Vec<String>>,
+    /// IFSC codes keyed by bank code and cleaned city or district (e.g., ("SBIN", "pune")), sorted
+    by_place: HashMap<(String, String), Vec<String>>,
+    /// Statistics of the load that built this index
+    report: LoadReport,
 }
 
 impl IFSC {
     /// Creates a new IFSC instance by loading and indexing IFSC data from JSON files.
     ///
-    /// This constructor scans the specified directory for JSON files containing IFSC
-    /// (Indian Financial System Code) data and builds an in-memory index for fast lookups.
-    /// Each JSON file should contain a mapping of IFSC codes to branch information.
+    /// This constructor reads the `.json` files containing IFSC (Indian Financial System
+    /// Code) data from the specified directory, or straight from a `.tar.gz`/`.tgz` or
+    /// `.zip` archive of them without extracting it, and builds an in-memory index for
+    /// fast lookups. Each JSON file should contain a mapping of IFSC codes to branch
+    /// information. Branches are also indexed by MICR code and by bank code and city or
+    /// district.
+    ///
+    /// Bank files in a directory are parsed in parallel, streaming from disk; archive
+    /// entries are streamed one after the other. Other files are ignored, and bank files
+    /// that cannot be read or parsed are skipped with a warning; both are counted in the
+    /// [`LoadReport`] returned by [`IFSC::load_report`].
     ///
     /// # Arguments
     ///
-    /// * `dir` - A path to the directory containing IFSC JSON data files.
-    ///           Can be any type that implements `AsRef<Path>` (e.g., `&str`, `String`, `Path`, `PathBuf`).
+    /// * `path` - A path to the directory or archive containing IFSC JSON data files.
+    ///   Can be any type that implements `AsRef<Path>` (e.g., `&str`, `String`, `Path`, `PathBuf`).
     ///
     /// # Returns
     ///
     /// * `Result<Self>` - Returns `Ok(IFSC)` on successful loading, or `Err` if:
-    ///   - The directory cannot be read
-    ///   - Any JSON file cannot be read or parsed
-    ///   - The JSON structure doesn't match the expected `Branch` format
+    ///   - The directory or archive cannot be read, or the path is neither
+    ///   - Every JSON file in it failed to load
     ///
     /// # File Format
     ///
@@ -77,37 +449,566 @@ impl IFSC {
     ///   }
     /// }
     /// ```
-    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self> {
-        let dir_path = dir.as_ref();
-        tracing::info!("IFSC data loading from {}", dir_path.display());
+    pub fn new<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let started = Instant::now();
+        tracing::info!("IFSC data loading from {}", path.display());
+
+        let mut report = LoadReport::default();
+        let mut branches = Vec::new();
+        if !path.exists() {
+            bail!("IFSC data path {} not found", path.display());
+        } else if path.is_dir() {
+            Self::read_dir(path, &mut report, &mut branches)
+                .with_context(|| format!("Error reading IFSC directory {}", path.display()))?;
+        } else if is_tar_gz(path) {
+            Self::read_tar_gz(path, &mut report, &mut branches)
+                .with_context(|| format!("Error reading IFSC archive {}", path.display()))?;
+        } else if is_zip(path) {
+            Self::read_zip(path, &mut report, &mut branches)
+                .with_context(|| format!("Error reading IFSC archive {}", path.display()))?;
+        } else {
+            bail!(
+                "IFSC data path {} is neither a directory nor a .tar.gz, .tgz or .zip archive",
+                path.display()
+            );
+        }
 
-        // Hashmap containing IFSC contents
-        let mut ifsc_data = HashMap::new();
-        let entries = fs::read_dir(dir_path)
-            .with_context(|| format!("Error reading IFSC directory {}", dir_path.display()))?;
+        if report.banks.is_empty() && !report.skipped.is_empty() {
+            bail!(
+                "None of the {} IFSC files in {} could be loaded",
+                report.skipped.len(),
+                path.display()
+            );
+        }
 
-        for entry in entries {
-            let entry = entry?; // resolve any possible errors
-            let path = entry.path();
+        Ok(Self::index(branches, report, started))
+    }
 
+    /// Reads the bank files of a directory, in parallel.
+    fn read_dir(dir: &Path, report: &mut LoadReport, branches: &mut Vec<Branch>) -> Result<()> {
+        let mut paths = Vec::new();
+        for entry in fs::read_dir(dir)? {
+            let path = entry?.path();
             if !path.is_file() {
                 continue;
             }
+            if is_json(&path) {
+                paths.push(path);
+            } else {
+                tracing::debug!("Ignoring non-JSON file {}", path.display());
+                report.ignored += 1;
+            }
+        }
+        paths.sort();
+
+        for (path, (result, elapsed)) in paths.iter().zip(read_banks_parallel(&paths)) {
+            report.record(branches, path, path.display().to_string(), result, elapsed);
+        }
+        Ok(())
+    }
 
-            let file_content = fs::read_to_string(&path)
-                .with_context(|| format!("Error reading IFSC json file: {}", path.display()))?;
+    /// Reads the bank files of a gzip-compressed tar archive, streaming each entry.
+    ///
+    /// A bank file that fails to parse is skipped, but a corrupt archive fails the load.
+    fn read_tar_gz(
+        archive_path: &Path,
+        report: &mut LoadReport,
+        branches: &mut Vec<Branch>,
+    ) -> Result<()> {
+        let mut archive = tar::Archive::new(GzDecoder::new(File::open(archive_path)?));
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            if !entry.header().entry_type().is_file() {
+                continue;
+            }
+            let path = entry.path()?.into_owned();
+            if !is_json(&path) {
+                tracing::debug!("Ignoring non-JSON archive entry {}", path.display());
+                report.ignored += 1;
+                continue;
+            }
 
-            // Unmarshall the json file
-            let branches: HashMap<String, Branch> = serde_json::from_str(&file_content)
-                .with_context(|| {
-                    format!("Error unmarshalling the IFSC json file {}", path.display())
-                })?;
+            let started = Instant::now();
+            let result = read_bank(&mut entry);
+            let source = format!("{}!{}", archive_path.display(), path.display());
+            report.record(branches, &path, source, result, started.elapsed());
+        }
+        Ok(())
+    }
 
-            for (_, branch) in branches {
-                ifsc_data.insert(branch.ifsc.clone(), branch);
+    /// Reads the bank files of a zip archive, streaming each entry.
+    ///
+    /// A bank file that fails to parse is skipped, but a corrupt archive fails the load.
+    fn read_zip(
+        archive_path: &Path,
+        report: &mut LoadReport,
+        branches: &mut Vec<Branch>,
+    ) -> Result<()> {
+        let mut archive = zip::ZipArchive::new(File::open(archive_path)?)?;
+        for index in 0..archive.len() {
+            let mut file = archive.by_index(index)?;
+            if !file.is_file() {
+                continue;
             }
+            let Some(path) = file.enclosed_name() else {
+                tracing::warn!("Ignoring IFSC archive entry {} with an unsafe path", index);
+                report.ignored += 1;
+                continue;
+            };
+            if !is_json(&path) {
+                tracing::debug!("Ignoring non-JSON archive entry {}", path.display());
+                report.ignored += 1;
+                continue;
+            }
+
+            let started = Instant::now();
+            let result = read_bank(&mut file);
+            let source = format!("{}!{}", archive_path.display(), path.display());
+            report.record(branches, &path, source, result, started.elapsed());
         }
-        tracing::info!("Loaded {} IFSC records", ifsc_data.len());
-        Ok(IFSC { data: ifsc_data })
+        Ok(())
+    }
+
+    /// Builds the lookup indexes over the loaded branches.
+    ///
+    /// When several branches share an IFSC code, the one loaded last wins.
+    fn index(branches: Vec<Branch>, mut report: LoadReport, started: Instant) -> Self {
+        let mut ifsc_data = HashMap::with_capacity(branches.len());
+        for branch in branches {
+            ifsc_data.insert(branch.ifsc.clone(), branch);
+        }
+
+        let mut banks: HashMap<String, BankSummary> = HashMap::new();
+        let mut by_micr: HashMap<String, Vec<String>> = HashMap::new();
+        let mut by_place: HashMap<(String, String), Vec<String>> = HashMap::new();
+        for (code, branch) in &ifsc_data {
+            let Some(prefix) = code.get(..BANK_CODE_LEN) else {
+                continue;
+            };
+            banks
+                .entry(prefix.to_string())
+                .or_insert_with(|| BankSummary {
+                    code: prefix.to_string(),
+                    name: branch.bank.clone(),
+                    ..Default::default()
+                })
+                .add(branch);
+
+            if is_micr(&branch.micr) {
+                by_micr
+                    .entry(branch.micr.clone())
+                    .or_default()
+                    .push(code.clone());
+            }
+
+            let city = clean_place(&branch.city);
+            let district = clean_place(&branch.district);
+            for place in [city, district]
+                .into_iter()
+                .filter(|place| !place.is_empty())
+            {
+                by_place
+                    .entry((prefix.to_string(), place))
+                    .or_default()
+                    .push(code.clone());
+            }
+        }
+        // The city and district are often the same, which lists a branch twice
+        for codes in by_micr.values_mut().chain(by_place.values_mut()) {
+            codes.sort_unstable();
+            codes.dedup();
+        }
+
+        report.elapsed = started.elapsed();
+        tracing::info!(
+            "Loaded {} IFSC records of {} banks from {} files in {:.2?} ({} skipped, {} ignored)",
+            ifsc_data.len(),
+            banks.len(),
+            report.banks.len(),
+            report.elapsed,
+            report.skipped.len(),
+            report.ignored
+        );
+        for stats in &report.banks {
+            tracing::debug!(
+                "Loaded {} {} branches from {} in {:.2?}",
+                stats.branches,
+                stats.bank_code,
+                stats.source,
+                stats.elapsed
+            );
+        }
+
+        IFSC {
+            data: ifsc_data,
+            banks,
+            by_micr,
+            by_place,
+            report,
+        }
+    }
+
+    /// Looks up a branch by its IFSC code.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - An 11 character IFSC code (case-insensitive)
+    ///
+    /// # Returns
+    ///
+    /// * `Option<&Branch>` - The matching branch, or `None` if the code is unknown
+    pub fn get(&self, code: &str) -> Option<&Branch> {
+        self.lookup(code).ok()
+    }
+
+    /// Looks up a branch by its IFSC code, explaining why none was found.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - An 11 character IFSC code (case-insensitive)
+    ///
+    /// # Returns
+    ///
+    /// * `Ok(&Branch)` - The matching branch
+    /// * `Err(LookupError::Malformed)` - If the code does not follow the IFSC format
+    /// * `Err(LookupError::Unknown)` - If no branch has the code, with the bank name
+    ///   inferred from its 4-letter prefix when other branches of the bank are loaded
+    pub fn lookup(&self, code: &str) -> Result<&Branch, LookupError> {
+        let code = validate(code)?;
+        self.data.get(&code).ok_or_else(|| LookupError::Unknown {
+            bank: self.bank_name(&code[..BANK_CODE_LEN]).map(String::from),
+            code,
+        })
+    }
+
+    /// Finds the branches with a MICR code.
+    ///
+    /// # Arguments
+    ///
+    /// * `micr` - A 9 digit MICR code
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<&Branch>` - The matching branches, sorted by IFSC code
+    pub fn find_by_micr(&self, micr: &str) -> Vec<&Branch> {
+        self.resolve(self.by_micr.get(micr))
+    }
+
+    /// Finds the branches of a bank in a city or district.
+    ///
+    /// # Arguments
+    ///
+    /// * `bank_code` - The 4-letter bank code (case-insensitive, e.g., "sbin")
+    /// * `place` - A city or district name; spaces, hyphens and case are ignored
+    ///
+    /// # Returns
+    ///
+    /// * `Vec<&Branch>` - The matching branches, sorted by IFSC code
+    pub fn find_by_place(&self, bank_code: &str, place: &str) -> Vec<&Branch> {
+        let key = (bank_code.to_ascii_uppercase(), clean_place(place));
+        self.resolve(self.by_place.get(&key))
+    }
+
+    /// Maps indexed IFSC codes back to their branches.
+    fn resolve(&self, codes: Option<&Vec<String>>) -> Vec<&Branch> {
+        codes
+            .into_iter()
+            .flatten()
+            .filter_map(|code| self.data.get(code))
+            .collect()
+    }
+
+    /// Returns the name of a bank by its 4-letter bank code (case-insensitive).
+    pub fn bank_name(&self, bank_code: &str) -> Option<&str> {
+        self.bank(bank_code).map(|summary| summary.name.as_str())
+    }
+
+    /// Returns the summary of a bank by its 4-letter bank code (case-insensitive).
+    pub fn bank(&self, bank_code: &str) -> Option<&BankSummary> {
+        self.banks.get(&bank_code.to_ascii_uppercase())
+    }
+
+    /// Returns the number of loaded banks.
+    pub fn bank_count(&self) -> usize {
+        self.banks.len()
+    }
+
+    /// Returns the number of loaded branches.
+    pub fn count(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns the statistics of the load, per bank file.
+    pub fn load_report(&self) -> &LoadReport {
+        &self.report
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixtures::{GOOD_BANK, KKBK_BANK, SBIN_BANK, load};
+    use super::*;
+    use tempfile::TempDir;
+
+    /// A bank file cut off in the middle.
+    const BROKEN_BANK: &str = r#"{"SBIN0000001": {"BANK": "State Bank"#;
+
+    /// A good bank file, a broken bank file and a README, as (name, contents).
+    const FIXTURE: [(&str, &str); 3] = [
+        ("HDFC.json", GOOD_BANK),
+        ("SBIN.json", BROKEN_BANK),
+        ("README.md", "IFSC data"),
+    ];
+
+    /// The IFSC codes of a list of branches.
+    fn codes(branches: Vec<&Branch>) -> Vec<&str> {
+        branches
+            .into_iter()
+            .map(|branch| branch.ifsc.as_str())
+            .collect()
+    }
+
+    /// Checks that the fixture loads the good bank and accounts for the other two files.
+    fn assert_fixture_loaded(ifsc: &IFSC) {
+        let report = ifsc.load_report();
+        assert_eq!(report.banks.len(), 1);
+        assert_eq!(report.banks[0].bank_code, "HDFC");
+        assert_eq!(report.banks[0].branches, 1);
+        assert_eq!(report.skipped.len(), 1);
+        assert!(report.skipped[0].0.ends_with("SBIN.json"));
+        assert_eq!(report.ignored, 1);
+
+        assert_eq!(ifsc.count(), 1);
+        assert_eq!(ifsc.lookup("hdfc0000001").unwrap().city, "MUMBAI");
+    }
+
+    #[test]
+    fn directory_load_skips_broken_and_ignores_other_files() {
+        let dir = TempDir::new().unwrap();
+        for (name, contents) in FIXTURE {
+            fs::write(dir.path().join(name), contents).unwrap();
+        }
+
+        assert_fixture_loaded(&IFSC::new(dir.path()).unwrap());
+    }
+
+    #[test]
+    fn tar_gz_load_skips_broken_and_ignores_other_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("by-bank.tar.gz");
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            File::create(&path).unwrap(),
+            flate2::Compression::default(),
+        ));
+        for (name, contents) in FIXTURE {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(
+                    &mut header,
+                    format!("by-bank/{}", name),
+                    contents.as_bytes(),
+                )
+                .unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+
+        assert_fixture_loaded(&IFSC::new(&path).unwrap());
+    }
+
+    #[test]
+    fn zip_load_skips_broken_and_ignores_other_files() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("by-bank.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&path).unwrap());
+        for (name, contents) in FIXTURE {
+            writer
+                .start_file(
+                    format!("by-bank/{}", name),
+                    zip::write::SimpleFileOptions::default(),
+                )
+                .unwrap();
+            std::io::Write::write_all(&mut writer, contents.as_bytes()).unwrap();
+        }
+        writer.finish().unwrap();
+
+        assert_fixture_loaded(&IFSC::new(&path).unwrap());
+    }
+
+    #[test]
+    fn corrupt_archives_and_other_files_fail() {
+        let dir = TempDir::new().unwrap();
+        let archive = dir.path().join("by-bank.tar.gz");
+        fs::write(&archive, b"not gzip").unwrap();
+        assert!(IFSC::new(&archive).is_err());
+
+        let other = dir.path().join("by-bank.rar");
+        fs::write(&other, b"").unwrap();
+        let error = IFSC::new(&other).err().unwrap();
+        assert!(
+            error.to_string().contains("neither a directory"),
+            "{:#}",
+            error
+        );
+    }
+
+    #[test]
+    fn directory_of_only_broken_files_fails() {
+        let dir = TempDir::new().unwrap();
+        fs::write(dir.path().join("SBIN.json"), BROKEN_BANK).unwrap();
+
+        assert!(IFSC::new(dir.path()).is_err());
+    }
+
+    fn reason(code: &str) -> &'static str {
+        match validate(code) {
+            Err(LookupError::Malformed { reason, .. }) => reason,
+            other => panic!("expected a malformed code, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn validate_uppercases_well_formed_codes() {
+        assert_eq!(validate("sbin0000001").unwrap(), "SBIN0000001");
+        assert_eq!(validate("HdFc0CaGsBk").unwrap(), "HDFC0CAGSBK");
+    }
+
+    #[test]
+    fn validate_explains_what_is_malformed() {
+        assert_eq!(reason(""), "must be 11 characters long");
+        assert_eq!(reason("sbin000001"), "must be 11 characters long");
+        assert_eq!(reason("sbin00000001"), "must be 11 characters long");
+        assert_eq!(
+            reason("sb1n0000001"),
+            "the first 4 characters (bank code) must be letters"
+        );
+        assert_eq!(reason("sbin1000001"), "the 5th character must be 0");
+        assert_eq!(
+            reason("sbin00000-1"),
+            "the last 6 characters (branch code) must be letters or digits"
+        );
+        // 11 bytes, but the last character is not ASCII
+        assert_eq!(
+            reason("sbin00000é"),
+            "the last 6 characters (branch code) must be letters or digits"
+        );
+    }
+
+    #[test]
+    fn lookup_errors_read_as_sentences() {
+        let error = validate("sbin1000001").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "malformed IFSC code 'sbin1000001': the 5th character must be 0"
+        );
+
+        let error = LookupError::Unknown {
+            code: "SBIN0999999".to_string(),
+            bank: Some("State Bank of India".to_string()),
+        };
+        assert_eq!(
+            error.to_string(),
+            "unknown IFSC code SBIN0999999 (bank: State Bank of India)"
+        );
+
+        let error = LookupError::Unknown {
+            code: "ABCD0000001".to_string(),
+            bank: None,
+        };
+        assert_eq!(
+            error.to_string(),
+            "unknown IFSC code ABCD0000001 (unknown bank ABCD)"
+        );
+
+        // Codes are only built by `lookup`, but a short or non-ASCII one must not panic
+        for code in ["AB", "ABCÀ0000001"] {
+            let error = LookupError::Unknown {
+                code: code.to_string(),
+                bank: None,
+            };
+            assert!(error.to_string().starts_with("unknown IFSC code "));
+        }
+    }
+
+    #[test]
+    fn find_by_micr_lists_every_branch_with_the_code() {
+        let ifsc = load(&[("SBIN.json", SBIN_BANK)]);
+
+        assert_eq!(codes(ifsc.find_by_micr("411002002")), ["SBIN0000454"]);
+        assert_eq!(
+            codes(ifsc.find_by_micr("411002003")),
+            ["SBIN0001110", "SBIN0002222"]
+        );
+        assert!(ifsc.find_by_micr("999999999").is_empty());
+        // Branches without a MICR code are not indexed under an empty one
+        assert!(ifsc.find_by_micr("").is_empty());
+    }
+
+    #[test]
+    fn find_by_place_matches_cities_and_districts() {
+        let ifsc = load(&[("SBIN.json", SBIN_BANK), ("HDFC.json", GOOD_BANK)]);
+
+        // Pimpri is in the city of Pimpri Chinchwad, in the district of Pune
+        assert_eq!(
+            codes(ifsc.find_by_place("sbin", "pune")),
+            ["SBIN0000454", "SBIN0001110", "SBIN0002222"]
+        );
+        assert_eq!(
+            codes(ifsc.find_by_place("SBIN", "pimpri-chinchwad")),
+            ["SBIN0002222"]
+        );
+        assert_eq!(
+            codes(ifsc.find_by_place("sbin", "navi-mumbai")),
+            ["SBIN0003333"]
+        );
+        assert_eq!(codes(ifsc.find_by_place("sbin", "thane")), ["SBIN0003333"]);
+        assert!(ifsc.find_by_place("sbin", "mumbai").is_empty());
+        assert!(ifsc.find_by_place("hdfc", "pune").is_empty());
+        assert!(ifsc.find_by_place("abcd", "pune").is_empty());
+    }
+
+    #[test]
+    fn payment_rails_treat_missing_fields_as_unsupported() {
+        let ifsc = load(&[("KKBK.json", KKBK_BANK), ("HDFC.json", GOOD_BANK)]);
+
+        let full = ifsc.get("KKBK0000001").unwrap();
+        assert_eq!(full.upi, Some(true));
+        assert_eq!(full.swift, "KKBKINBBXXX");
+        assert_eq!(full.payment_rails(), Some([true; 5]));
+
+        // Null and missing flags are unknown, which counts as unsupported
+        let partial = ifsc.get("KKBK0000002").unwrap();
+        assert_eq!(
+            (partial.upi, partial.imps, partial.neft, partial.rtgs),
+            (Some(false), None, Some(true), None)
+        );
+        assert_eq!(partial.swift, "");
+        assert_eq!(
+            partial.payment_rails(),
+            Some([false, false, true, false, false])
+        );
+
+        assert_eq!(ifsc.get("KKBK0000003").unwrap().payment_rails(), None);
+        assert_eq!(ifsc.get("HDFC0000001").unwrap().payment_rails(), None);
+    }
+
+    #[test]
+    fn bank_summaries_count_branches_per_rail() {
+        let ifsc = load(&[("KKBK.json", KKBK_BANK), ("HDFC.json", GOOD_BANK)]);
+        assert_eq!(ifsc.bank_count(), 2);
+
+        let kotak = ifsc.bank("kkbk").unwrap();
+        assert_eq!(kotak.code, "KKBK");
+        assert_eq!(kotak.name, "Kotak Mahindra Bank");
+        assert_eq!(kotak.branches, 3);
+        assert_eq!(kotak.rails, [1, 1, 2, 1, 1]);
+
+        let hdfc = ifsc.bank("HDFC").unwrap();
+        assert_eq!(hdfc.branches, 1);
+        assert_eq!(hdfc.rails, [0; 5]);
+        assert!(ifsc.bank("SBIN").is_none());
     }
 }
@@ -1,3 +1,4 @@
+pub mod config;
 pub mod handlers;
 pub mod ifsc;
 pub mod services;
@@ -1,14 +1,12 @@
-use std::net::{IpAddr, Ipv4Addr, SocketAddr};
-use std::str::FromStr;
+use std::net::{IpAddr, SocketAddr};
 
 use anyhow::Result;
 
-use hickory_proto::rr::LowerName;
-
 use tokio::net::UdpSocket;
 
 use hickory_server::ServerFuture;
 
+use rdns_toys::config::Config;
 use rdns_toys::handlers::{DnsHandlers, RdnsRequestHandler};
 use rdns_toys::services;
 
@@ -20,17 +18,29 @@ async fn main() -> Result<()> {
         tracing_subscriber::fmt::init();
     }
 
-    let domain = "localhost";
-    let port = 8053;
-
-    println!("🚀 Starting rdns-toys DNS server on {}:{}", domain, port);
+    // Load configuration (first argument, defaults to config.toml)
+    let config_path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| "config.toml".to_string());
+    let config = Config::load(&config_path)?;
+
+    let address: IpAddr = config.server.address.parse()?;
+    let port = config.server.port;
+
+    println!("🚀 Starting rdns-toys DNS server on {}:{}", address, port);
+    for zone in &config.zones {
+        println!(
+            "🌐 Serving zone {} ({})",
+            zone.domain,
+            zone.services.join(", ")
+        );
+    }
 
     // Create DNS handlers
-    let domain_name = LowerName::from_str(domain)?;
-    let mut handlers = DnsHandlers::new(domain_name.clone())?;
+    let mut handlers = DnsHandlers::new();
 
-    // Register all services
-    services::register_services(&mut handlers)?;
+    // Register all zones and their services
+    services::register_services(&mut handlers, &config)?;
 
     // Create our custom request handler
     let request_handler = RdnsRequestHandler::new(handlers);
@@ -39,14 +49,10 @@ async fn main() -> Result<()> {
     let mut server = ServerFuture::new(request_handler);
 
     // Bind UDP socket
-    let udp_socket = UdpSocket::bind(SocketAddr::new(
-        IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1)),
-        port,
-    ))
-    .await?;
+    let udp_socket = UdpSocket::bind(SocketAddr::new(address, port)).await?;
     server.register_socket(udp_socket);
 
-    println!(" DNS server listening on 127.0.0.1:{} (UDP only)", port);
+    println!(" DNS server listening on {}:{} (UDP only)", address, port);
     println!("⏹️  Press Ctrl+C to stop");
 
     // Start the server
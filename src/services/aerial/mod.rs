@@ -0,0 +1,285 @@
+//! # Aerial Distance DNS Service
+//!
+//! This module computes great-circle distances between two places through DNS queries.
+//! Places are given either as city names (resolved through the geo service) or as raw
+//! latitude/longitude pairs.
+
+use crate::handlers::{Answer, ResponseBuilder, Service, Ttls};
+use crate::services::geo::geodesy::{WGS84_A, WGS84_F, haversine_km};
+use crate::services::geo::{Geo, parse_coordinates};
+use anyhow::Result;
+use async_trait::async_trait;
+use hickory_proto::rr::{Name, RecordType};
+use hickory_server::server::Request;
+use std::sync::Arc;
+
+/// Default TTL of aerial answers, distances between fixed points never change
+pub const AERIAL_TTL: u32 = 3600;
+
+/// Kilometers per statute mile
+const KM_PER_MILE: f64 = 1.609344;
+
+/// Maximum iterations before the Vincenty formula is considered not to converge
+const VINCENTY_MAX_ITERATIONS: usize = 200;
+
+/// Computes the distance between two points on the WGS84 ellipsoid (Vincenty's inverse formula).
+///
+/// This is accurate to within millimeters, but the iteration may fail to converge for
+/// nearly antipodal points.
+///
+/// # Arguments
+/// * `lat1`, `lon1` - The first point in decimal degrees
+/// * `lat2`, `lon2` - The second point in decimal degrees
+///
+/// # Returns
+/// * `Some(f64)` - The distance in kilometers
+/// * `None` - If the formula does not converge
+pub fn vincenty_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> Option<f64> {
+    let b = WGS84_A * (1.0 - WGS84_F);
+    let l = (lon2 - lon1).to_radians();
+    let u1 = ((1.0 - WGS84_F) * lat1.to_radians().tan()).atan();
+    let u2 = ((1.0 - WGS84_F) * lat2.to_radians().tan()).atan();
+    let (sin_u1, cos_u1) = u1.sin_cos();
+    let (sin_u2, cos_u2) = u2.sin_cos();
+
+    let mut lambda = l;
+    for _ in 0..VINCENTY_MAX_ITERATIONS {
+        let (sin_lambda, cos_lambda) = lambda.sin_cos();
+        let sin_sigma = ((cos_u2 * sin_lambda).powi(2)
+            + (cos_u1 * sin_u2 - sin_u1 * cos_u2 * cos_lambda).powi(2))
+        .sqrt();
+        if sin_sigma == 0.0 {
+            // Coincident points
+            return Some(0.0);
+        }
+
+        let cos_sigma = sin_u1 * sin_u2 + cos_u1 * cos_u2 * cos_lambda;
+        let sigma = sin_sigma.atan2(cos_sigma);
+        let sin_alpha = cos_u1 * cos_u2 * sin_lambda / sin_sigma;
+        let cos_sq_alpha = 1.0 - sin_alpha.powi(2);
+        // Both points on the equator make cos_sq_alpha zero
+        let cos_2sigma_m = if cos_sq_alpha == 0.0 {
+            0.0
+        } else {
+            cos_sigma - 2.0 * sin_u1 * sin_u2 / cos_sq_alpha
+        };
+        let c = WGS84_F / 16.0 * cos_sq_alpha * (4.0 + WGS84_F * (4.0 - 3.0 * cos_sq_alpha));
+
+        let previous = lambda;
+        lambda = l
+            + (1.0 - c)
+                * WGS84_F
+                * sin_alpha
+                * (sigma
+                    + c * sin_sigma
+                        * (cos_2sigma_m + c * cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))));
+
+        if (lambda - previous).abs() < 1e-12 {
+            let u_sq = cos_sq_alpha * (WGS84_A.powi(2) - b.powi(2)) / b.powi(2);
+            let big_a =
+                1.0 + u_sq / 16384.0 * (4096.0 + u_sq * (-768.0 + u_sq * (320.0 - 175.0 * u_sq)));
+            let big_b = u_sq / 1024.0 * (256.0 + u_sq * (-128.0 + u_sq * (74.0 - 47.0 * u_sq)));
+            let delta_sigma = big_b
+                * sin_sigma
+                * (cos_2sigma_m
+                    + big_b / 4.0
+                        * (cos_sigma * (-1.0 + 2.0 * cos_2sigma_m.powi(2))
+                            - big_b / 6.0
+                                * cos_2sigma_m
+                                * (-3.0 + 4.0 * sin_sigma.powi(2))
+                                * (-3.0 + 4.0 * cos_2sigma_m.powi(2))));
+
+            return Some(b * big_a * (sigma - delta_sigma) / 1000.0);
+        }
+    }
+
+    None
+}
+
+/// Computes the initial bearing (forward azimuth) from the first point to the second.
+///
+/// # Arguments
+/// * `lat1`, `lon1` - The starting point in decimal degrees
+/// * `lat2`, `lon2` - The destination in decimal degrees
+///
+/// # Returns
+/// * `f64` - The bearing in degrees clockwise from true north, in `[0, 360)`
+pub fn initial_bearing(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let delta_lambda = (lon2 - lon1).to_radians();
+
+    let y = delta_lambda.sin() * phi2.cos();
+    let x = phi1.cos() * phi2.sin() - phi1.sin() * phi2.cos() * delta_lambda.cos();
+    (y.atan2(x).to_degrees() + 360.0) % 360.0
+}
+
+/// A resolved endpoint of a distance query.
+#[derive(Debug, Clone)]
+struct Place {
+    /// Display label (city name or the raw coordinates)
+    label: String,
+    latitude: f64,
+    longitude: f64,
+}
+
+/// DNS service returning the aerial distance between two places.
+///
+/// Supported query formats:
+/// - Two cities separated by a hyphen, each accepting the `city/cc` filter
+///   (e.g., `dig mumbai-delhi.aerial` or `dig london/gb-paris.aerial`)
+/// - Two coordinate pairs separated by a slash
+///   (e.g., `dig 12.9352,77.6245/12.9698,77.7500.aerial`)
+///
+/// The answer contains the haversine (spherical) and Vincenty (ellipsoidal) distances in
+/// kilometers and miles, plus the initial bearing from the first place to the second.
+pub struct AerialService {
+    geo: Arc<Geo>,
+    ttls: Ttls,
+}
+
+impl AerialService {
+    /// Creates a new AerialService instance.
+    ///
+    /// # Arguments
+    /// * `geo` - The loaded location index, shared with the geo service
+    /// * `ttls` - TTLs of the returned records, per record type
+    pub fn new(geo: Arc<Geo>, ttls: Ttls) -> Self {
+        Self { geo, ttls }
+    }
+
+    /// Parses a "lat,lon" pair into a place, validating the coordinate ranges.
+    fn parse_place(text: &str) -> Option<Place> {
+        let (latitude, longitude) = parse_coordinates(text)?;
+        Some(Place {
+            label: format!("{},{}", latitude, longitude),
+            latitude,
+            longitude,
+        })
+    }
+
+    /// Resolves a city name to its most populous matching location.
+    fn resolve_city(&self, text: &str) -> Option<Place> {
+        let location = self.geo.query(text)?.into_iter().next()?;
+        Some(Place {
+            label: format!("{} ({})", location.name, location.country),
+            latitude: location.latitude,
+            longitude: location.longitude,
+        })
+    }
+
+    /// Parses a query into its two endpoints.
+    ///
+    /// Queries containing a comma are treated as coordinate pairs separated by a slash,
+    /// everything else as two city names separated by a hyphen.
+    fn parse_places(&self, query: &str) -> Option<(Place, Place)> {
+        if query.contains(',') {
+            let (from, to) = query.split_once('/')?;
+            Some((Self::parse_place(from)?, Self::parse_place(to)?))
+        } else {
+            let (from, to) = query.split_once('-')?;
+            Some((self.resolve_city(from)?, self.resolve_city(to)?))
+        }
+    }
+
+    /// Formats the distance and bearing between two places.
+    ///
+    /// # Example
+    /// ```ignore
+    /// assert_eq!(
+    ///     txt,
+    ///     "Mumbai (IN) -> Delhi (IN): haversine 1152.99 km (716.43 mi), \
+    ///      vincenty 1149.40 km (714.21 mi), bearing 21.70 deg"
+    /// );
+    /// ```
+    fn format_distance(from: &Place, to: &Place) -> String {
+        let haversine = haversine_km(from.latitude, from.longitude, to.latitude, to.longitude);
+        let vincenty = match vincenty_km(from.latitude, from.longitude, to.latitude, to.longitude) {
+            Some(km) => format!("{:.2} km ({:.2} mi)", km, km / KM_PER_MILE),
+            None => "n/a".to_string(),
+        };
+        let bearing = initial_bearing(from.latitude, from.longitude, to.latitude, to.longitude);
+
+        format!(
+            "{} -> {}: haversine {:.2} km ({:.2} mi), vincenty {}, bearing {:.2} deg",
+            from.label,
+            to.label,
+            haversine,
+            haversine / KM_PER_MILE,
+            vincenty,
+            bearing
+        )
+    }
+}
+
+#[async_trait]
+impl Service for AerialService {
+    /// Handles aerial distance queries between two cities or coordinate pairs.
+    ///
+    /// ## Arguments
+    /// * `request` - The DNS request
+    /// * `query_name` - The DNS name being queried
+    /// * `query_type` - The type of DNS record requested (only `TXT` is supported)
+    /// * `cleaned_query` - The cleaned query string (e.g., "mumbai-delhi")
+    ///
+    /// ## Returns
+    /// * `Some(Answer)` - A TXT record with the distances and bearing
+    /// * `None` - If the query type is not supported or a place cannot be resolved
+    async fn query(
+        &self,
+        _request: &Request,
+        query_name: &Name,
+        query_type: RecordType,
+        cleaned_query: &str,
+    ) -> Option<Answer> {
+        if query_type != RecordType::TXT {
+            return None;
+        }
+
+        let (from, to) = self.parse_places(cleaned_query)?;
+
+        let mut response = ResponseBuilder::new(query_name, &self.ttls);
+        response.txt(&Self::format_distance(&from, &to));
+        response.build()
+    }
+
+    /// Exports service information for debugging or monitoring.
+    async fn dump(&self) -> Result<Vec<u8>> {
+        let service_info = "Aerial service - Returns the distance and bearing between two places";
+        Ok(service_info.as_bytes().to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn vincenty_matches_the_reference_geodesic() {
+        // Flinders Peak to Buninyong, the worked example of Vincenty's 1975 paper
+        let km = vincenty_km(-37.951033417, 144.424867889, -37.652821139, 143.926495528).unwrap();
+        assert!((km - 54.972271).abs() < 1e-6, "got {}", km);
+    }
+
+    #[test]
+    fn vincenty_handles_coincident_and_equatorial_points() {
+        assert_eq!(vincenty_km(12.97, 77.59, 12.97, 77.59), Some(0.0));
+
+        // One degree of longitude along the equator is a·π/180 on the ellipsoid
+        let km = vincenty_km(0.0, 0.0, 0.0, 1.0).unwrap();
+        assert!((km - 111.319491).abs() < 1e-6, "got {}", km);
+    }
+
+    #[test]
+    fn vincenty_gives_up_on_nearly_antipodal_points() {
+        assert_eq!(vincenty_km(0.0, 0.0, 0.5, 179.7), None);
+    }
+
+    #[test]
+    fn vincenty_stays_close_to_haversine() {
+        // Mumbai to Delhi: the spherical model is off by well under 1%
+        let (lat1, lon1, lat2, lon2) = (19.07283, 72.88261, 28.65195, 77.23149);
+        let ellipsoidal = vincenty_km(lat1, lon1, lat2, lon2).unwrap();
+        let spherical = haversine_km(lat1, lon1, lat2, lon2);
+        assert!((ellipsoidal - spherical).abs() / ellipsoidal < 0.01);
+    }
+}
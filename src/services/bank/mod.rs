@@ -0,0 +1,190 @@
+//! # Bank DNS Service
+//!
+//! This module summarizes the Indian banks of the IFSC data indexed by
+//! [`crate::ifsc::IFSC`] through DNS queries, looking banks up by the 4-letter code
+//! starting their IFSC codes.
+
+use crate::handlers::{Answer, DnsHandlers, ResponseBuilder, Service, Ttls};
+use crate::ifsc::{BankSummary, IFSC, PAYMENT_RAILS, is_bank_code};
+use anyhow::Result;
+use async_trait::async_trait;
+use hickory_proto::rr::{Name, RecordType};
+use hickory_server::server::Request;
+use std::sync::Arc;
+
+/// DNS service returning a summary of an Indian bank.
+///
+/// Banks are looked up by their 4-letter bank code, case-insensitively
+/// (e.g., `dig hdfc.bank`). The answer contains TXT records with the bank name, the
+/// number of loaded branches and how many of them support each payment rail
+/// (UPI, IMPS, NEFT, RTGS and SWIFT).
+///
+/// Malformed and unknown bank codes are answered with an `error:` TXT record.
+pub struct BankService {
+    ifsc: Arc<IFSC>,
+    ttls: Ttls,
+}
+
+impl BankService {
+    /// Creates a new BankService instance.
+    ///
+    /// # Arguments
+    /// * `ifsc` - The loaded IFSC index, shared with the ifsc service
+    /// * `ttls` - TTLs of the returned records, per record type
+    pub fn new(ifsc: Arc<IFSC>, ttls: Ttls) -> Self {
+        Self { ifsc, ttls }
+    }
+
+    /// Formats a bank summary as human-readable lines, one per TXT record.
+    ///
+    /// Payment rails that no branch supports are left out.
+    ///
+    /// # Example
+    /// ```ignore
+    /// assert_eq!(
+    ///     lines,
+    ///     [
+    ///         "HDFC Bank (HDFC)",
+    ///         "Branches: 6130",
+    ///         "Payment rails: UPI (6130 branches), IMPS (6130 branches), NEFT (6130 branches), RTGS (6130 branches), SWIFT (1 branch)",
+    ///     ]
+    /// );
+    /// ```
+    fn format_bank(bank: &BankSummary) -> Vec<String> {
+        let rails: Vec<_> = PAYMENT_RAILS
+            .iter()
+            .zip(bank.rails)
+            .filter(|(_, count)| *count > 0)
+            .map(|(rail, count)| {
+                let plural = if count == 1 { "branch" } else { "branches" };
+                format!("{} ({} {})", rail, count, plural)
+            })
+            .collect();
+
+        vec![
+            format!("{} ({})", bank.name, bank.code),
+            format!("Branches: {}", bank.branches),
+            format!(
+                "Payment rails: {}",
+                if rails.is_empty() {
+                    "none known".to_string()
+                } else {
+                    rails.join(", ")
+                }
+            ),
+        ]
+    }
+}
+
+#[async_trait]
+impl Service for BankService {
+    /// Handles bank code queries, returning a summary of the matching bank.
+    ///
+    /// ## Arguments
+    /// * `request` - The DNS request
+    /// * `query_name` - The DNS name being queried
+    /// * `query_type` - The type of DNS record requested (only `TXT` is supported)
+    /// * `cleaned_query` - The cleaned query string (e.g., "hdfc")
+    ///
+    /// ## Returns
+    /// * `Some(Answer)` - TXT records summarizing the bank, or an error record if
+    ///   the code is malformed or unknown
+    /// * `None` - If the query type is not supported
+    async fn query(
+        &self,
+        _request: &Request,
+        query_name: &Name,
+        query_type: RecordType,
+        cleaned_query: &str,
+    ) -> Option<Answer> {
+        if query_type != RecordType::TXT {
+            return None;
+        }
+
+        if !is_bank_code(cleaned_query) {
+            return Some(DnsHandlers::create_error_response(
+                query_name,
+                &format!("malformed bank code '{}': must be 4 letters", cleaned_query),
+            ));
+        }
+        let Some(bank) = self.ifsc.bank(cleaned_query) else {
+            return Some(DnsHandlers::create_error_response(
+                query_name,
+                &format!("unknown bank code {}", cleaned_query.to_ascii_uppercase()),
+            ));
+        };
+
+        let mut response = ResponseBuilder::new(query_name, &self.ttls);
+        for line in Self::format_bank(bank) {
+            response.txt(&line);
+        }
+        response.build()
+    }
+
+    /// Dumps service statistics for debugging purposes.
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>>` - Service summary as bytes
+    async fn dump(&self) -> Result<Vec<u8>> {
+        let summary = format!(
+            "Bank service with {} banks and {} branches",
+            self.ifsc.bank_count(),
+            self.ifsc.count()
+        );
+        Ok(summary.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::test_request;
+    use crate::ifsc::fixtures::{GOOD_BANK, KKBK_BANK, load};
+    use std::str::FromStr;
+
+    /// Answers a TXT query, returning the text of each record.
+    async fn answer(service: &BankService, query: &str) -> Vec<String> {
+        let name = Name::from_str(&format!("{}.bank.localhost.", query)).unwrap();
+        let request = test_request(&name, RecordType::TXT, None);
+
+        service
+            .query(&request, &name, RecordType::TXT, query)
+            .await
+            .unwrap()
+            .records
+            .iter()
+            .map(|record| record.data().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn bank_service_summarizes_the_bank() {
+        let ifsc = load(&[("KKBK.json", KKBK_BANK), ("HDFC.json", GOOD_BANK)]);
+        let service = BankService::new(Arc::new(ifsc), Ttls::new(60));
+
+        assert_eq!(
+            answer(&service, "kkbk").await,
+            [
+                "Kotak Mahindra Bank (KKBK)",
+                "Branches: 3",
+                "Payment rails: UPI (1 branch), IMPS (1 branch), NEFT (2 branches), RTGS (1 branch), SWIFT (1 branch)",
+            ]
+        );
+        assert_eq!(
+            answer(&service, "hdfc").await,
+            [
+                "HDFC Bank (HDFC)",
+                "Branches: 1",
+                "Payment rails: none known"
+            ]
+        );
+        assert_eq!(
+            answer(&service, "sbin").await,
+            ["error: unknown bank code SBIN"]
+        );
+        assert_eq!(
+            answer(&service, "hdf1").await,
+            ["error: malformed bank code 'hdf1': must be 4 letters"]
+        );
+    }
+}
@@ -0,0 +1,434 @@
+//! # Country DNS Service
+//!
+//! This module provides information about countries through DNS queries, loaded from the
+//! geonames.org `countryInfo.txt` file. Countries can be looked up by ISO code or name,
+//! and the loaded data is also used by the geo service to show full country names.
+
+use crate::handlers::{Answer, ResponseBuilder, Service, Ttls};
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use hickory_proto::rr::{Name, RecordType};
+use hickory_server::server::Request;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashMap;
+use std::fs::File;
+use std::sync::Arc;
+
+/// Default TTL of country answers, the data only changes with the data file
+pub const COUNTRY_TTL: u32 = 3600;
+
+/// Minimum number of columns of a `countryInfo.txt` row (up to the neighbours column)
+const MIN_COLUMNS: usize = 18;
+
+/// Regex pattern used to clean country names and queries for consistent lookups.
+/// Removes everything except ASCII letters.
+static RE_CLEAN: Lazy<Regex> = Lazy::new(|| Regex::new(r"[^a-z]+").unwrap());
+
+/// Lowercases a name and strips everything but letters (e.g., "United States" becomes
+/// "unitedstates", matching the `united-states` DNS label).
+fn clean_text(text: &str) -> String {
+    RE_CLEAN.replace_all(&text.to_lowercase(), "").to_string()
+}
+
+/// Returns the name of a geonames continent code.
+fn continent_name(code: &str) -> &str {
+    match code {
+        "AF" => "Africa",
+        "AN" => "Antarctica",
+        "AS" => "Asia",
+        "EU" => "Europe",
+        "NA" => "North America",
+        "OC" => "Oceania",
+        "SA" => "South America",
+        _ => code,
+    }
+}
+
+/// Represents a country with the information from the geonames.org country data.
+#[derive(Debug, Clone)]
+pub struct Country {
+    /// Two-letter ISO 3166 code (e.g., "IN")
+    pub iso: String,
+    /// Three-letter ISO 3166 code (e.g., "IND")
+    pub iso3: String,
+    /// English name of the country
+    pub name: String,
+    /// Name of the capital city, empty if the country has none
+    pub capital: String,
+    /// Area in square kilometers
+    pub area_km2: f64,
+    /// Population count
+    pub population: u64,
+    /// Two-letter continent code (e.g., "AS")
+    pub continent: String,
+    /// Country code top-level domain, including the leading dot (e.g., ".in")
+    pub tld: String,
+    /// ISO 4217 currency code (e.g., "INR")
+    pub currency_code: String,
+    /// Currency name (e.g., "Rupee")
+    pub currency_name: String,
+    /// International calling code without the leading "+" (e.g., "91")
+    pub phone: String,
+    /// Spoken languages as language tags, most common first (e.g., ["en-IN", "hi"])
+    pub languages: Vec<String>,
+    /// Two-letter codes of the neighbouring countries
+    pub neighbours: Vec<String>,
+}
+
+/// Index of every country, searchable by ISO code and name.
+#[derive(Debug, Default)]
+pub struct Countries {
+    /// All loaded countries, in file order
+    countries: Vec<Country>,
+    /// Maps lowercase ISO codes and cleaned names to indices into `countries`
+    index: HashMap<String, usize>,
+}
+
+impl Countries {
+    /// Creates a new Countries instance by loading a geonames.org `countryInfo.txt` file.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to the country data file
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The loaded countries or an error if the file cannot be read
+    ///
+    /// # Example
+    /// ```ignore
+    /// let countries = Countries::new("data/countryInfo.txt")?;
+    /// ```
+    pub fn new(file_path: &str) -> Result<Self> {
+        let countries = Self::read_file(file_path)
+            .with_context(|| format!("Failed to read country data from '{}'", file_path))?;
+
+        let mut index = HashMap::new();
+        // Codes are indexed first so they win over a country name of the same spelling
+        for (position, country) in countries.iter().enumerate() {
+            index.insert(country.iso.to_lowercase(), position);
+            index.insert(country.iso3.to_lowercase(), position);
+        }
+        for (position, country) in countries.iter().enumerate() {
+            index.entry(clean_text(&country.name)).or_insert(position);
+        }
+
+        Ok(Self { countries, index })
+    }
+
+    /// Reads and parses a geonames.org `countryInfo.txt` file into a collection of Country structs.
+    ///
+    /// Comment lines starting with `#` (including the column header) are skipped.
+    pub fn read_file(file_path: &str) -> Result<Vec<Country>> {
+        let file = File::open(file_path)?;
+        let mut rdr = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(false)
+            .flexible(true)
+            .quoting(false)
+            .comment(Some(b'#'))
+            .from_reader(file);
+
+        let split_list = |text: &str| -> Vec<String> {
+            text.split(',')
+                .map(str::trim)
+                .filter(|item| !item.is_empty())
+                .map(String::from)
+                .collect()
+        };
+
+        let mut countries = Vec::new();
+        for result in rdr.records() {
+            let record = result?;
+            if record.len() < MIN_COLUMNS {
+                continue;
+            }
+
+            let country = (|| {
+                Some(Country {
+                    iso: record.get(0)?.to_string(),
+                    iso3: record.get(1)?.to_string(),
+                    name: record.get(4)?.to_string(),
+                    capital: record.get(5)?.to_string(),
+                    area_km2: record.get(6)?.parse().unwrap_or(0.0),
+                    population: record.get(7)?.parse().unwrap_or(0),
+                    continent: record.get(8)?.to_string(),
+                    tld: record.get(9)?.to_string(),
+                    currency_code: record.get(10)?.to_string(),
+                    currency_name: record.get(11)?.to_string(),
+                    phone: record.get(12)?.trim_start_matches('+').to_string(),
+                    languages: split_list(record.get(15)?),
+                    neighbours: split_list(record.get(17)?),
+                })
+            })();
+
+            if let Some(country) = country.filter(|c| c.iso.len() == 2) {
+                countries.push(country);
+            }
+        }
+        Ok(countries)
+    }
+
+    /// Looks up a country by its two-letter ISO code (case-insensitive).
+    pub fn get(&self, iso: &str) -> Option<&Country> {
+        self.index
+            .get(&iso.to_lowercase())
+            .map(|&position| &self.countries[position])
+            .filter(|country| country.iso.eq_ignore_ascii_case(iso))
+    }
+
+    /// Looks up a country by ISO code or name.
+    ///
+    /// # Arguments
+    /// * `q` - A two- or three-letter ISO code or a country name (e.g., "in", "ind",
+    ///   "india" or "united-states")
+    ///
+    /// # Returns
+    /// * `Option<&Country>` - The matching country, if any
+    pub fn query(&self, q: &str) -> Option<&Country> {
+        self.index
+            .get(&clean_text(q))
+            .map(|&position| &self.countries[position])
+    }
+
+    /// Returns the number of loaded countries.
+    pub fn count(&self) -> usize {
+        self.countries.len()
+    }
+}
+
+/// DNS service returning information about a country.
+///
+/// Countries are looked up by ISO code or name (e.g., `dig in.country`,
+/// `dig ind.country` or `dig india.country`); multi-word names are written with
+/// hyphens (e.g., `dig united-states.country`). The answer is a set of TXT records
+/// with the capital, population, area, currency, calling code, TLD, languages and
+/// neighbours.
+pub struct CountryService {
+    countries: Arc<Countries>,
+    ttls: Ttls,
+}
+
+impl CountryService {
+    /// Creates a new CountryService instance.
+    ///
+    /// # Arguments
+    /// * `countries` - The loaded country data, shared with the geo service
+    /// * `ttls` - TTLs of the returned records, per record type
+    pub fn new(countries: Arc<Countries>, ttls: Ttls) -> Self {
+        Self { countries, ttls }
+    }
+
+    /// Formats a country as human-readable lines, one per TXT record.
+    ///
+    /// Lines for empty fields (e.g., the neighbours of an island) are left out.
+    ///
+    /// # Example
+    /// ```ignore
+    /// assert_eq!(
+    ///     lines,
+    ///     [
+    ///         "India (IN, IND) - Capital: New Delhi, Continent: Asia",
+    ///         "Population: 1352617328, Area: 3287590 km2",
+    ///         "Currency: INR (Rupee), Calling code: +91, TLD: .in",
+    ///         "Languages: en-IN, hi, bn, te, mr, ta, ur, gu, kn, ml, or, pa, as, bh, sat, ks, ne, sd, kok, doi, mni, sit, sa, fr, lus, inc",
+    ///         "Neighbours: CN, NP, MM, BT, PK, BD",
+    ///     ]
+    /// );
+    /// ```
+    pub fn format_country(&self, country: &Country) -> Vec<String> {
+        let mut lines = vec![
+            format!(
+                "{} ({}, {}) - Capital: {}, Continent: {}",
+                country.name,
+                country.iso,
+                country.iso3,
+                if country.capital.is_empty() {
+                    "none"
+                } else {
+                    &country.capital
+                },
+                continent_name(&country.continent)
+            ),
+            format!(
+                "Population: {}, Area: {} km2",
+                country.population, country.area_km2
+            ),
+        ];
+
+        let mut contact = Vec::new();
+        if !country.currency_code.is_empty() {
+            contact.push(format!(
+                "Currency: {} ({})",
+                country.currency_code, country.currency_name
+            ));
+        }
+        if !country.phone.is_empty() {
+            contact.push(format!("Calling code: +{}", country.phone));
+        }
+        if !country.tld.is_empty() {
+            contact.push(format!("TLD: {}", country.tld));
+        }
+        if !contact.is_empty() {
+            lines.push(contact.join(", "));
+        }
+
+        if !country.languages.is_empty() {
+            lines.push(format!("Languages: {}", country.languages.join(", ")));
+        }
+        if !country.neighbours.is_empty() {
+            lines.push(format!("Neighbours: {}", country.neighbours.join(", ")));
+        }
+        lines
+    }
+}
+
+#[async_trait]
+impl Service for CountryService {
+    /// Handles country queries by ISO code or name.
+    ///
+    /// ## Arguments
+    /// * `request` - The DNS request
+    /// * `query_name` - The DNS name being queried
+    /// * `query_type` - The type of DNS record requested (only `TXT` is supported)
+    /// * `cleaned_query` - The cleaned query string (e.g., "in" or "india")
+    ///
+    /// ## Returns
+    /// * `Some(Answer)` - TXT records describing the country
+    /// * `None` - If the query type is not supported or no country matches
+    async fn query(
+        &self,
+        _request: &Request,
+        query_name: &Name,
+        query_type: RecordType,
+        cleaned_query: &str,
+    ) -> Option<Answer> {
+        if query_type != RecordType::TXT {
+            return None;
+        }
+
+        let country = self.countries.query(cleaned_query)?;
+
+        let mut response = ResponseBuilder::new(query_name, &self.ttls);
+        for line in self.format_country(country) {
+            response.txt(&line);
+        }
+        response.build()
+    }
+
+    /// Dumps service statistics for debugging purposes.
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>>` - Service summary as bytes
+    async fn dump(&self) -> Result<Vec<u8>> {
+        let summary = format!("Country service with {} countries", self.countries.count());
+        Ok(summary.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::test_request;
+    use std::io::Write;
+    use std::str::FromStr;
+    use tempfile::NamedTempFile;
+
+    /// A trimmed-down `countryInfo.txt`, with its comment lines and column header.
+    const COUNTRY_INFO: &str = "\
+# GeoNames.org Country Information
+# ================================
+#ISO\tISO3\tISO-Numeric\tfips\tCountry\tCapital\tArea(in sq km)\tPopulation\tContinent\ttld\tCurrencyCode\tCurrencyName\tPhone\tPostal Code Format\tPostal Code Regex\tLanguages\tgeonameid\tneighbours\tEquivalentFipsCode
+IN\tIND\t356\tIN\tIndia\tNew Delhi\t3287590\t1352617328\tAS\t.in\tINR\tRupee\t91\t######\t^(\\d{6})$\ten-IN,hi,bn\t1269750\tCN,NP,MM,BT,PK,BD\t
+US\tUSA\t840\tUS\tUnited States\tWashington\t9629091\t327167434\tNA\t.us\tUSD\tDollar\t1\t#####-####\t^\\d{5}(-\\d{4})?$\ten-US,es-US,haw,fr\t6252001\tCA,MX,CU\t
+AQ\tATA\t010\tAY\tAntarctica\t\t1.4E7\t0\tAN\t.aq\t\t\t\t\t\t\t6697173\t\t
+XX\ttoo\tfew\tcolumns
+";
+
+    fn countries() -> Countries {
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(COUNTRY_INFO.as_bytes()).unwrap();
+        Countries::new(file.path().to_str().unwrap()).unwrap()
+    }
+
+    async fn answer(service: &CountryService, query: &str) -> Option<Vec<String>> {
+        let name = Name::from_str(&format!("{}.country.localhost.", query)).unwrap();
+        let request = test_request(&name, RecordType::TXT, None);
+
+        let records = service
+            .query(&request, &name, RecordType::TXT, query)
+            .await?;
+        Some(
+            records
+                .records
+                .iter()
+                .map(|record| record.data().to_string())
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn read_file_skips_comments_and_short_rows() {
+        let countries = countries();
+        assert_eq!(countries.count(), 3);
+
+        let india = countries.get("in").unwrap();
+        assert_eq!(india.iso3, "IND");
+        assert_eq!(india.capital, "New Delhi");
+        assert_eq!(india.population, 1_352_617_328);
+        assert_eq!(india.languages, ["en-IN", "hi", "bn"]);
+        assert_eq!(india.neighbours, ["CN", "NP", "MM", "BT", "PK", "BD"]);
+
+        let antarctica = countries.get("AQ").unwrap();
+        assert_eq!(antarctica.area_km2, 14_000_000.0);
+        assert!(antarctica.neighbours.is_empty());
+    }
+
+    #[test]
+    fn query_matches_codes_and_names() {
+        let countries = countries();
+        for q in ["in", "IN", "ind", "india", "India"] {
+            assert_eq!(countries.query(q).unwrap().iso, "IN", "{}", q);
+        }
+        for q in [
+            "us",
+            "usa",
+            "united-states",
+            "unitedstates",
+            "United States",
+        ] {
+            assert_eq!(countries.query(q).unwrap().iso, "US", "{}", q);
+        }
+        assert!(countries.query("xx").is_none());
+        assert!(countries.query("united").is_none());
+
+        // `get` only accepts two-letter codes
+        assert!(countries.get("ind").is_none());
+        assert!(countries.get("india").is_none());
+    }
+
+    #[tokio::test]
+    async fn service_lists_the_country_details() {
+        let service = CountryService::new(Arc::new(countries()), Ttls::new(COUNTRY_TTL));
+
+        assert_eq!(
+            answer(&service, "united-states").await.unwrap(),
+            [
+                "United States (US, USA) - Capital: Washington, Continent: North America",
+                "Population: 327167434, Area: 9629091 km2",
+                "Currency: USD (Dollar), Calling code: +1, TLD: .us",
+                "Languages: en-US, es-US, haw, fr",
+                "Neighbours: CA, MX, CU",
+            ]
+        );
+        // Empty fields are left out
+        assert_eq!(
+            answer(&service, "aq").await.unwrap(),
+            [
+                "Antarctica (AQ, ATA) - Capital: none, Continent: Antarctica",
+                "Population: 0, Area: 14000000 km2",
+                "TLD: .aq",
+            ]
+        );
+        assert!(answer(&service, "atlantis").await.is_none());
+    }
+}
@@ -0,0 +1,30 @@
+//! # Geodesy
+//!
+//! Earth models and distance formulas shared by the geo-backed services.
+
+/// Mean earth radius in kilometers, used by the haversine formula
+pub const EARTH_RADIUS_KM: f64 = 6371.0088;
+
+/// Semi-major axis of the WGS84 ellipsoid in meters
+pub const WGS84_A: f64 = 6_378_137.0;
+
+/// Flattening of the WGS84 ellipsoid
+pub const WGS84_F: f64 = 1.0 / 298.257_223_563;
+
+/// Computes the great-circle distance between two points on a spherical earth.
+///
+/// # Arguments
+/// * `lat1`, `lon1` - The first point in decimal degrees
+/// * `lat2`, `lon2` - The second point in decimal degrees
+///
+/// # Returns
+/// * `f64` - The distance in kilometers
+pub fn haversine_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (phi1, phi2) = (lat1.to_radians(), lat2.to_radians());
+    let delta_phi = (lat2 - lat1).to_radians();
+    let delta_lambda = (lon2 - lon1).to_radians();
+
+    let a = (delta_phi / 2.0).sin().powi(2)
+        + phi1.cos() * phi2.cos() * (delta_lambda / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().atan2((1.0 - a).sqrt())
+}
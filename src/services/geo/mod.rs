@@ -3,23 +3,37 @@
 //! This module provides DNS-based access to the geolocation service,
 //! allowing users to query geographic information via DNS queries.
 
-use crate::handlers::Service;
+pub mod geodesy;
+mod search;
+mod snapshot;
+mod spatial;
+
+use crate::handlers::{Answer, LOC_RECORD_TYPE, ResponseBuilder, Service, Ttls};
+use crate::services::country::Countries;
 use anyhow::{Context, Result};
 use async_trait::async_trait;
 use chrono_tz::Tz;
-use hickory_proto::rr::{Name, RData, Record, RecordType, rdata};
+use hickory_proto::rr::{Name, RecordType};
 use hickory_server::server::Request;
 use once_cell::sync::Lazy;
 use regex::Regex;
 use std::cmp::Reverse;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::str::FromStr;
+use std::sync::Arc;
+use unicode_normalization::UnicodeNormalization;
+use unicode_normalization::char::is_combining_mark;
+
+use geodesy::haversine_km;
+use search::SearchIndex;
+use spatial::KdTree;
 
 /// Represents a geographic location with timezone and population data.
 ///
 /// This struct contains all the essential information about a city or location
-/// that can be queried through the DNS service.
+/// that can be queried through the DNS service. Country, timezone and region strings
+/// are interned, so locations sharing them point to the same allocation.
 #[derive(Debug, Clone)]
 pub struct Location {
     /// Unique identifier from the geonames.org database
@@ -30,60 +44,253 @@ pub struct Location {
     pub latitude: f64,
     /// Longitude coordinate in decimal degrees
     pub longitude: f64,
+    /// Elevation in meters: the surveyed elevation if known, else the digital elevation
+    /// model value (0 when neither is available)
+    pub elevation: i32,
     /// Timezone name (e.g., "America/New_York")
-    pub timezone_name: String,
+    pub timezone_name: Arc<str>,
     /// Population count for the location
     pub population: u64,
     /// Parsed timezone object for time calculations
     pub timezone: Tz,
     /// Two-letter country code (e.g., "US", "CA")
-    pub country: String,
+    pub country: Arc<str>,
+    /// First-level administrative division code (e.g., "IL" for Illinois, "16" for Maharashtra)
+    pub admin1: Arc<str>,
+    /// Name of the first-level administrative division, when admin1 names are loaded
+    pub region: Option<Arc<str>>,
+}
+
+impl Location {
+    /// Returns the region name, falling back to the admin1 code.
+    ///
+    /// # Returns
+    /// * `Option<&str>` - The region (e.g., "Illinois" or "IL"), or None if the location has no admin1 code
+    pub fn region_label(&self) -> Option<&str> {
+        self.region
+            .as_deref()
+            .or((!self.admin1.is_empty()).then_some(&*self.admin1))
+    }
+}
+
+/// Deduplicates repeated strings such as country codes and timezone names.
+#[derive(Debug, Default)]
+struct Interner {
+    strings: HashSet<Arc<str>>,
+}
+
+impl Interner {
+    /// Returns the shared copy of a string, storing it on first use.
+    fn intern(&mut self, text: &str) -> Arc<str> {
+        if let Some(shared) = self.strings.get(text) {
+            return Arc::clone(shared);
+        }
+        let shared: Arc<str> = Arc::from(text);
+        self.strings.insert(Arc::clone(&shared));
+        shared
+    }
+}
+
+/// Other names of each location, keyed by geonames id.
+pub type AlternateNames = HashMap<String, Vec<String>>;
+
+/// Sources of the names a location can be queried by, besides its ASCII name and
+/// timezone city.
+#[derive(Debug, Clone, Default)]
+pub struct NameSources {
+    /// Index the `alternatenames` column of the cities file. It carries no language
+    /// information, so it is only used when `alternate_names_path` is not set.
+    pub inline: bool,
+    /// Path to a geonames `alternateNamesV2.txt` (or `alternateNames.txt`) file
+    pub alternate_names_path: Option<String>,
+    /// ISO language codes kept from the alternate names file; empty keeps every language
+    pub languages: Vec<String>,
+    /// Path to a geonames `admin1CodesASCII.txt` file, used to name regions
+    pub admin1_path: Option<String>,
 }
 
+/// Pseudo-language codes of the alternate names file that are not place names
+/// (links, postal codes, airport codes, etc.).
+const NON_NAME_LANGUAGES: [&str; 8] = [
+    "link", "wkdt", "post", "iata", "icao", "faac", "unlc", "tcid",
+];
+
 /// Core geolocation service that manages location data and provides querying capabilities.
 ///
-/// The Geo struct maintains an internal mapping of location names to Location objects,
-/// allowing for efficient lookups by city name or timezone alias.
-#[derive(Debug)]
+/// The Geo struct stores every Location once and maps location names to their indices,
+/// allowing for efficient lookups by city name, alternate name or timezone alias, a search index for
+/// prefix and misspelled names, and a spatial index for finding the locations nearest
+/// to a coordinate.
+#[derive(Debug, Default)]
 pub struct Geo {
-    /// Maps cleaned location names to vectors of matching locations
-    tz_map: HashMap<String, Vec<Location>>,
+    /// Maps cleaned location names to the indices of matching locations, most populous first
+    tz_map: HashMap<String, Vec<u32>>,
+    /// All loaded locations, in load order; every other structure refers to them by index
+    locations: Vec<Location>,
+    /// Prefix and fuzzy index over the `tz_map` keys
+    search: SearchIndex,
+    /// Spatial index over `locations`
+    spatial: KdTree,
     /// Total number of locations loaded
     count: usize,
 }
 
 /// Regex pattern used to clean location names for consistent lookups.
-/// Removes all non-alphabetic characters except forward slashes.
-static RE_CLEAN: Lazy<Regex> = Lazy::new(|| Regex::new("[^a-z/]+").unwrap());
+/// Removes everything except letters (of any script), combining marks and forward slashes.
+static RE_CLEAN: Lazy<Regex> = Lazy::new(|| Regex::new(r"[^\p{L}\p{M}/]+").unwrap());
+
+/// Lowercases a name and strips everything but letters, for use as a lookup key.
+fn clean_text(text: &str) -> String {
+    RE_CLEAN.replace_all(&text.to_lowercase(), "").to_string()
+}
+
+/// Strips diacritics from a cleaned key (e.g., "münchen" becomes "munchen").
+///
+/// # Returns
+/// * `Some(String)` - The folded key, if it differs from the input and is plain ASCII
+/// * `None` - If there is nothing to fold or the key uses a non-Latin script
+fn fold_diacritics(key: &str) -> Option<String> {
+    if key.is_ascii() {
+        return None;
+    }
+    let folded: String = key.nfd().filter(|c| !is_combining_mark(*c)).collect();
+    folded.is_ascii().then_some(folded)
+}
+
+/// Decodes internationalized (punycode) labels such as "xn--2scrj9c" back to Unicode.
+fn decode_idna(text: &str) -> String {
+    if !text.contains("xn--") {
+        return text.to_string();
+    }
+    Name::from_ascii(text)
+        .map(|name| name.to_utf8())
+        .unwrap_or_else(|_| text.to_string())
+}
+
+/// Maximum number of locations returned when a query has no exact match
+const MAX_SEARCH_RESULTS: usize = 5;
+
+/// Parses a "lat,lon" coordinate pair in decimal degrees.
+///
+/// # Arguments
+/// * `text` - The coordinate pair (e.g., "19.07,72.87" or "-33.86,151.2")
+///
+/// # Returns
+/// * `Some((f64, f64))` - The latitude and longitude
+/// * `None` - If the text is not a pair of numbers or a value is out of range
+pub fn parse_coordinates(text: &str) -> Option<(f64, f64)> {
+    let (lat, lon) = text.split_once(',')?;
+    let latitude: f64 = lat.parse().ok()?;
+    let longitude: f64 = lon.parse().ok()?;
+
+    if !(-90.0..=90.0).contains(&latitude) || !(-180.0..=180.0).contains(&longitude) {
+        return None;
+    }
+
+    Some((latitude, longitude))
+}
 
 impl Geo {
     /// Creates a new Geo instance by loading location data from a file.
     ///
     /// # Arguments
     /// * `file_path` - Path to the geonames.org cities data file
+    /// * `sources` - Where to read alternate names from
     ///
     /// # Returns
-    /// * `Result<Self>` - A new Geo instance or an error if file cannot be read
+    /// * `Result<Self>` - A new Geo instance or an error if a file cannot be read
     ///
     /// # Example
+    /// ```ignore
+    /// let geo = Geo::new("data/cities15000.txt", &NameSources::default())?;
     /// ```
-    /// let geo = Geo::new("data/cities15000.txt")?;
-    /// ```
-    pub fn new(file_path: &str) -> Result<Self> {
-        let locations = Self::read_file(file_path)
+    pub fn new(file_path: &str, sources: &NameSources) -> Result<Self> {
+        let inline = sources.inline && sources.alternate_names_path.is_none();
+        let (mut locations, mut names) = Self::read_file(file_path, inline)
             .with_context(|| format!("Failed to read the geonames.org file at '{}'", file_path))?;
 
-        let mut geo = Self {
-            tz_map: HashMap::new(),
-            count: 0,
+        if let Some(path) = &sources.admin1_path {
+            let regions: HashMap<String, Arc<str>> = Self::read_admin1(path)
+                .with_context(|| format!("Failed to read the admin1 codes file at '{}'", path))?
+                .into_iter()
+                .map(|(code, name)| (code, Arc::from(name)))
+                .collect();
+            for location in &mut locations {
+                let code = format!("{}.{}", location.country, location.admin1);
+                location.region = regions.get(&code).cloned();
+            }
+        }
+
+        if let Some(path) = &sources.alternate_names_path {
+            let ids: HashSet<&str> = locations.iter().map(|loc| loc.id.as_str()).collect();
+            let alternates = Self::read_alternate_names(path, &sources.languages, &ids)
+                .with_context(|| {
+                    format!("Failed to read the alternate names file at '{}'", path)
+                })?;
+            for (id, list) in alternates {
+                names.entry(id).or_default().extend(list);
+            }
+        }
+
+        let mut geo = Self::default();
+        geo.load(locations, &names);
+        Ok(geo)
+    }
+
+    /// Loads the index from a binary snapshot, falling back to the geonames.org files.
+    ///
+    /// When the snapshot is missing, corrupt, from another format version or older than
+    /// the source files, the index is built with [`Geo::new`] and a fresh snapshot is
+    /// written for the next start.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to the geonames.org cities data file
+    /// * `sources` - Where to read alternate names from
+    /// * `snapshot_path` - Path of the snapshot, or None to always parse the data files
+    ///
+    /// # Returns
+    /// * `Result<Self>` - The loaded index or an error if the data files cannot be read
+    ///
+    /// # Example
+    /// ```ignore
+    /// let geo = Geo::open("data/cities15000.txt", &sources, Some("data/geo.snapshot"))?;
+    /// ```
+    pub fn open(
+        file_path: &str,
+        sources: &NameSources,
+        snapshot_path: Option<&str>,
+    ) -> Result<Self> {
+        let Some(snapshot_path) = snapshot_path else {
+            return Self::new(file_path, sources);
         };
 
-        geo.load(locations);
+        let fingerprint = snapshot::fingerprint(file_path, sources)?;
+        match snapshot::read(snapshot_path, &fingerprint) {
+            Ok(geo) => {
+                tracing::info!("Loaded geo index from snapshot {}", snapshot_path);
+                return Ok(geo);
+            }
+            Err(e) => tracing::info!("Rebuilding geo index: {:#}", e),
+        }
+
+        let geo = Self::new(file_path, sources)?;
+        match snapshot::write(&geo, snapshot_path, &fingerprint) {
+            Ok(()) => tracing::info!("Wrote geo snapshot {}", snapshot_path),
+            Err(e) => tracing::warn!("Could not write geo snapshot: {:#}", e),
+        }
         Ok(geo)
     }
 
     /// Reads and parses a geonames.org cities data file into a collection of Location structs.
-    pub fn read_file(file_path: &str) -> Result<Vec<Location>> {
+    ///
+    /// The other names of each location are collected in the same pass: the ASCII
+    /// `asciiname` column always, the comma-separated `alternatenames` column only when
+    /// `inline` is set.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to the geonames.org cities data file
+    /// * `inline` - Whether to include the `alternatenames` column
+    pub fn read_file(file_path: &str, inline: bool) -> Result<(Vec<Location>, AlternateNames)> {
         let file = File::open(file_path)?;
         let mut rdr = csv::ReaderBuilder::new()
             .delimiter(b'\t')
@@ -91,6 +298,8 @@ impl Geo {
             .from_reader(file);
 
         let mut locations: Vec<Location> = Vec::new();
+        let mut names = AlternateNames::new();
+        let mut interner = Interner::default();
 
         for result in rdr.records() {
             let record = result?;
@@ -103,9 +312,23 @@ impl Geo {
                 let name = record.get(2)?.trim().split('(').next()?.trim().to_string();
                 let latitude = record.get(4)?.parse::<f64>().ok()?;
                 let longitude = record.get(5)?.parse::<f64>().ok()?;
-                let country = record.get(8)?.to_string();
+                // The dem column uses -9999 for points without elevation data
+                let elevation = record
+                    .get(15)?
+                    .parse::<i32>()
+                    .ok()
+                    .or_else(|| {
+                        record
+                            .get(16)?
+                            .parse::<i32>()
+                            .ok()
+                            .filter(|&dem| dem != -9999)
+                    })
+                    .unwrap_or(0);
+                let country = interner.intern(record.get(8)?);
+                let admin1 = interner.intern(record.get(10)?);
                 let population = record.get(14)?.parse::<u64>().ok()?;
-                let timezone_name = record.get(17)?.to_string();
+                let timezone_name = interner.intern(record.get(17)?);
                 let timezone = Tz::from_str(&timezone_name).ok()?;
 
                 Some(Location {
@@ -113,7 +336,10 @@ impl Geo {
                     name,
                     latitude,
                     longitude,
+                    elevation,
                     country,
+                    admin1,
+                    region: None,
                     population,
                     timezone_name,
                     timezone,
@@ -121,99 +347,287 @@ impl Geo {
             })();
 
             if let Some(loc) = location {
+                let mut list = vec![record[1].to_string()];
+                if inline {
+                    list.extend(
+                        record[3]
+                            .split(',')
+                            .filter(|name| !name.is_empty())
+                            .map(String::from),
+                    );
+                }
+                names.insert(loc.id.clone(), list);
                 locations.push(loc);
             }
         }
-        Ok(locations)
+        Ok((locations, names))
+    }
+
+    /// Reads region names from a geonames.org `admin1CodesASCII.txt` file.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to the admin1 codes file
+    ///
+    /// # Returns
+    /// * `Result<HashMap<String, String>>` - ASCII region names keyed by "CC.code" (e.g., "US.IL")
+    pub fn read_admin1(file_path: &str) -> Result<HashMap<String, String>> {
+        let file = File::open(file_path)?;
+        let mut rdr = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(false)
+            .flexible(true)
+            .quoting(false)
+            .from_reader(file);
+
+        let mut regions = HashMap::new();
+        for result in rdr.records() {
+            let record = result?;
+            if let (Some(code), Some(name)) = (record.get(0), record.get(2)) {
+                regions.insert(code.to_string(), name.to_string());
+            }
+        }
+        Ok(regions)
+    }
+
+    /// Reads a geonames.org alternate names file, keeping only the given locations.
+    ///
+    /// # Arguments
+    /// * `file_path` - Path to `alternateNamesV2.txt` or `alternateNames.txt`
+    /// * `languages` - ISO language codes to keep; empty keeps every language
+    /// * `ids` - Geonames ids of the loaded locations
+    pub fn read_alternate_names(
+        file_path: &str,
+        languages: &[String],
+        ids: &HashSet<&str>,
+    ) -> Result<AlternateNames> {
+        let file = File::open(file_path)?;
+        // Names may contain quote characters, so quoting is disabled
+        let mut rdr = csv::ReaderBuilder::new()
+            .delimiter(b'\t')
+            .has_headers(false)
+            .flexible(true)
+            .quoting(false)
+            .from_reader(file);
+
+        let mut names = AlternateNames::new();
+        for result in rdr.records() {
+            let record = result?;
+            let (Some(id), Some(language), Some(name)) =
+                (record.get(1), record.get(2), record.get(3))
+            else {
+                continue;
+            };
+
+            if !ids.contains(id) || NON_NAME_LANGUAGES.contains(&language) {
+                continue;
+            }
+            if !languages.is_empty() && !languages.iter().any(|l| l.eq_ignore_ascii_case(language))
+            {
+                continue;
+            }
+
+            names
+                .entry(id.to_string())
+                .or_default()
+                .push(name.to_string());
+        }
+        Ok(names)
     }
 
     /// Loads a collection of locations into the internal data structure.
     ///
     /// This method processes the locations, creates searchable mappings,
-    /// and sorts locations by population for better query results.
+    /// sorts locations by population for better query results and rebuilds
+    /// the search and spatial indexes used by [`Geo::query`] and [`Geo::nearest`].
     ///
     /// # Arguments
     /// * `locations` - Vector of Location objects to load
-    pub fn load(&mut self, locations: Vec<Location>) {
-        let clean_text =
-            |text: &str| -> String { RE_CLEAN.replace_all(&text.to_lowercase(), "").to_string() };
-
-        for location in &locations {
-            let city_key = clean_text(&location.name);
-            self.tz_map
-                .entry(city_key)
-                .or_default()
-                .push(location.clone());
+    /// * `names` - Other names of the locations, keyed by geonames id
+    pub fn load(&mut self, locations: Vec<Location>, names: &AlternateNames) {
+        let start = self.locations.len();
+        self.locations.extend(locations);
 
-            if let Some(city_alias) = location.timezone_name.split('/').nth(1) {
-                let alias_key = clean_text(city_alias);
-                self.tz_map
-                    .entry(alias_key)
-                    .or_default()
-                    .push(location.clone());
+        for (index, location) in self.locations.iter().enumerate().skip(start) {
+            let city_alias = location.timezone_name.split('/').nth(1);
+            let other_names = names.get(&location.id).into_iter().flatten();
+
+            let mut keys: Vec<String> = Vec::new();
+            for name in [location.name.as_str()]
+                .into_iter()
+                .chain(city_alias)
+                .chain(other_names.map(String::as_str))
+            {
+                let key = clean_text(name);
+                let folded = fold_diacritics(&key);
+                for key in [Some(key), folded].into_iter().flatten() {
+                    // Avoid listing a location twice under the same key
+                    if !key.is_empty() && !keys.contains(&key) {
+                        keys.push(key);
+                    }
+                }
+            }
+
+            for key in keys {
+                self.tz_map.entry(key).or_default().push(index as u32);
             }
 
             self.count += 1;
         }
 
-        for location_list in self.tz_map.values_mut() {
-            location_list.sort_unstable_by_key(|loc| Reverse(loc.population));
+        for indices in self.tz_map.values_mut() {
+            indices.sort_unstable_by_key(|&i| Reverse(self.locations[i as usize].population));
         }
+
+        self.search = SearchIndex::build(self.tz_map.keys());
+        self.spatial = KdTree::build(
+            self.locations
+                .iter()
+                .map(|loc| (loc.latitude, loc.longitude)),
+        );
     }
 
     /// Queries for locations matching the given search string.
     ///
-    /// Supports both city name queries and city/country format queries.
+    /// Supports city name queries, city/country queries and city/country/region
+    /// queries, where the region is an admin1 code or region name.
     /// Results are sorted by population (largest first).
     ///
+    /// When no name matches exactly, locations whose name starts with the query or
+    /// is within a couple of typos of it are returned instead, ranked by similarity
+    /// then population and capped at [`MAX_SEARCH_RESULTS`].
+    ///
     /// # Arguments
-    /// * `q` - Query string, optionally in "city/country" format
+    /// * `q` - Query string, optionally in "city/country" or "city/country/region" format
     ///
     /// # Returns
-    /// * `Option<Vec<Location>>` - Matching locations or None if no matches
+    /// * `Option<Vec<&Location>>` - Matching locations or None if no matches
     ///
     /// # Examples
-    /// ```
+    /// ```ignore
     /// // Query by city name
     /// let locations = geo.query("new york");
     ///
     /// // Query by city and country
     /// let locations = geo.query("london/uk");
+    ///
+    /// // Query by city, country and region
+    /// let locations = geo.query("springfield/us/il");
+    ///
+    /// // Misspelled or partial names
+    /// let locations = geo.query("mumbay");
+    /// let locations = geo.query("sanfran");
     /// ```
-    pub fn query(&self, q: &str) -> Option<Vec<Location>> {
-        let (query_str, country_filter) = if let Some((city, country)) = q.split_once('/') {
-            if country.len() == 2 {
-                (city, Some(country.to_uppercase()))
-            } else {
-                (q, None)
+    pub fn query(&self, q: &str) -> Option<Vec<&Location>> {
+        let mut parts = q.splitn(3, '/');
+        let city = parts.next().unwrap_or_default();
+        let (query_str, country_filter, region_filter) = match parts.next() {
+            Some(country) if country.len() == 2 => {
+                (city, Some(country.to_uppercase()), parts.next())
             }
-        } else {
-            (q, None)
+            _ => (q, None, None),
         };
 
-        let cleaned_query = RE_CLEAN
-            .replace_all(&query_str.to_lowercase(), "")
-            .to_string();
+        let cleaned_query = clean_text(&decode_idna(query_str));
 
-        let locations = self.tz_map.get(&cleaned_query)?;
+        let in_country = |loc: &Location| {
+            let country_matches = country_filter
+                .as_ref()
+                .is_none_or(|country| *loc.country == **country);
+            // Admin1 codes may be numeric, so they are compared before cleaning
+            let region_matches = region_filter.is_none_or(|region| {
+                loc.admin1.eq_ignore_ascii_case(region)
+                    || loc
+                        .region
+                        .as_deref()
+                        .is_some_and(|name| clean_text(name) == clean_text(region))
+            });
+            country_matches && region_matches
+        };
 
-        if let Some(country) = country_filter {
-            let filtered: Vec<Location> = locations
+        let filtered: Vec<&Location> = match self.tz_map.get(&cleaned_query) {
+            Some(indices) => indices
                 .iter()
-                .filter(|loc| loc.country == country)
-                .cloned()
-                .collect();
+                .map(|&i| &self.locations[i as usize])
+                .filter(|loc| in_country(loc))
+                .collect(),
+            None => self.search(&cleaned_query, in_country),
+        };
 
-            if filtered.is_empty() {
-                None
-            } else {
-                Some(filtered)
-            }
+        if filtered.is_empty() {
+            None
         } else {
-            Some(locations.clone())
+            Some(filtered)
         }
     }
 
+    /// Ranks the locations of prefix and fuzzy name matches.
+    ///
+    /// # Arguments
+    /// * `cleaned_query` - The cleaned query without country or region filters
+    /// * `keep` - Predicate applied to each location before the result cap
+    ///
+    /// # Returns
+    /// * `Vec<&Location>` - Up to [`MAX_SEARCH_RESULTS`] distinct locations, most similar first
+    fn search(&self, cleaned_query: &str, keep: impl Fn(&Location) -> bool) -> Vec<&Location> {
+        let mut ranked: Vec<(f64, u32)> = self
+            .search
+            .matches(cleaned_query)
+            .into_iter()
+            .flat_map(|(key, similarity)| {
+                self.tz_map
+                    .get(key)
+                    .into_iter()
+                    .flatten()
+                    .map(move |&i| (similarity, i))
+            })
+            .filter(|&(_, i)| keep(&self.locations[i as usize]))
+            .collect();
+
+        let population = |i: u32| self.locations[i as usize].population;
+        ranked.sort_by(|&(a_sim, a), &(b_sim, b)| {
+            b_sim
+                .total_cmp(&a_sim)
+                .then(population(b).cmp(&population(a)))
+        });
+
+        // A location is listed under both its name and its timezone alias
+        let mut seen = HashSet::new();
+        ranked
+            .into_iter()
+            .filter(|&(_, i)| seen.insert(i))
+            .take(MAX_SEARCH_RESULTS)
+            .map(|(_, i)| &self.locations[i as usize])
+            .collect()
+    }
+
+    /// Finds the locations closest to a coordinate.
+    ///
+    /// # Arguments
+    /// * `latitude` - Latitude in decimal degrees
+    /// * `longitude` - Longitude in decimal degrees
+    /// * `limit` - Maximum number of locations to return
+    ///
+    /// # Returns
+    /// * `Vec<(&Location, f64)>` - Matching locations with their great-circle distance in
+    ///   kilometers, closest first
+    ///
+    /// # Example
+    /// ```ignore
+    /// let nearest = geo.nearest(19.07, 72.87, 5);
+    /// ```
+    pub fn nearest(&self, latitude: f64, longitude: f64, limit: usize) -> Vec<(&Location, f64)> {
+        self.spatial
+            .nearest(latitude, longitude, limit)
+            .into_iter()
+            .map(|index| {
+                let location = &self.locations[index];
+                let distance =
+                    haversine_km(latitude, longitude, location.latitude, location.longitude);
+                (location, distance)
+            })
+            .collect()
+    }
+
     /// Returns the total number of locations loaded in the service.
     ///
     /// # Returns
@@ -226,28 +640,48 @@ impl Geo {
 /// DNS service wrapper for the geolocation functionality.
 ///
 /// This service allows users to query geographic information through DNS queries.
-/// It supports both TXT and A record types, providing location data in different formats.
+/// TXT queries return human-readable location data, LOC queries the coordinates as
+/// LOC records (RFC 1876). Other record types get an empty (NODATA) answer.
+/// TXT queries for a "lat,lon" coordinate (e.g., `dig 19.07,72.87.geo`) return the
+/// nearest locations instead.
+///
+/// When country data is given, TXT answers include the full country name next to the code.
 pub struct GeoService {
-    geo: Geo,
+    geo: Arc<Geo>,
+    ttls: Ttls,
+    nearest_results: usize,
+    countries: Option<Arc<Countries>>,
 }
 
+/// Default TTL of geo answers
+pub const GEO_TTL: u32 = 60;
+
+/// Default number of locations returned for a coordinate query
+pub const GEO_NEAREST_RESULTS: usize = 5;
+
 impl GeoService {
     /// Creates a new GeoService instance.
     ///
     /// # Arguments
-    /// * `data_path` - Path to the geonames.org cities data file
+    /// * `geo` - The loaded location index, shared with other geo-backed services
+    /// * `ttls` - TTLs of the returned records, per record type
+    /// * `nearest_results` - Maximum number of locations returned for a coordinate query
+    /// * `countries` - Optional country data, used to show full country names
     ///
     /// # Returns
-    /// * `Result<Self>` - A new GeoService instance or an error
-    pub fn new(data_path: &str) -> Result<Self> {
-        let geo = Geo::new(data_path).with_context(|| {
-            format!(
-                "Failed to initialize geo service with data from '{}'",
-                data_path
-            )
-        })?;
-
-        Ok(Self { geo })
+    /// * `Self` - A new GeoService instance
+    pub fn new(
+        geo: Arc<Geo>,
+        ttls: Ttls,
+        nearest_results: usize,
+        countries: Option<Arc<Countries>>,
+    ) -> Self {
+        Self {
+            geo,
+            ttls,
+            nearest_results,
+            countries,
+        }
     }
 
     /// Formats location data as a human-readable string for TXT records.
@@ -259,29 +693,49 @@ impl GeoService {
     /// * `String` - Formatted location information
     ///
     /// # Example
-    /// ```
+    /// ```ignore
     /// let location = Location {
     ///     id: "123".to_string(),
     ///     name: "Mumbai".to_string(),
     ///     latitude: 19.0760,
     ///     longitude: 72.8777,
-    ///     timezone_name: "Asia/Kolkata".to_string(),
+    ///     elevation: 8,
+    ///     timezone_name: "Asia/Kolkata".into(),
     ///     population: 20411000,
     ///     timezone: chrono_tz::Asia::Kolkata,
-    ///     country: "IN".to_string(),
+    ///     country: "IN".into(),
+    ///     admin1: "16".into(),
+    ///     region: Some("Maharashtra".into()),
     /// };
     /// let geo_service = GeoService { geo: geo_instance }; // assume geo_instance is a valid Geo
     /// let txt = geo_service.format_location_txt(&location);
     /// assert_eq!(
     ///     txt,
-    ///     "Mumbai (IN) - Pop: 20411000, TZ: Asia/Kolkata, Lat: 19.08, Lon: 72.88"
+    ///     "Mumbai (IN, Maharashtra) - Pop: 20411000, TZ: Asia/Kolkata, Lat: 19.0760, Lon: 72.8777"
     /// );
+    /// // With country data loaded:
+    /// // "Mumbai (IN, Maharashtra, India) - Pop: 20411000, ..."
     /// ```
     fn format_location_txt(&self, location: &Location) -> String {
+        let country_name = self
+            .countries
+            .as_ref()
+            .and_then(|countries| countries.get(&location.country))
+            .map(|country| country.name.as_str());
+        let place: Vec<&str> = [
+            Some(&*location.country),
+            location.region_label(),
+            country_name,
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        let place = place.join(", ");
+
         format!(
             "{} ({}) - Pop: {}, TZ: {}, Lat: {:.4}, Lon: {:.4}",
             location.name,
-            location.country,
+            place,
             location.population,
             location.timezone_name,
             location.latitude,
@@ -289,98 +743,76 @@ impl GeoService {
         )
     }
 
-    /// Formats location data as a comma-separated string for A records.
-    ///
-    /// # Arguments
-    /// * `location` - The location to format
-    ///
-    /// # Returns
-    /// * `String` - Comma-separated location data (name,country,lat,lon)
+    /// Formats a location found by a coordinate query, prefixed with its distance.
     ///
     /// # Example
-    /// ```
-    /// let location = Location {
-    ///     id: "123".to_string(),
-    ///     name: "Mumbai".to_string(),
-    ///     latitude: 19.0760,
-    ///     longitude: 72.8777,
-    ///     timezone_name: "Asia/Kolkata".to_string(),
-    ///     population: 20411000,
-    ///     timezone: chrono_tz::Asia::Kolkata,
-    ///     country: "IN".to_string(),
-    /// };
-    /// let geo_service = GeoService { geo: geo_instance }; // assume geo_instance is a valid Geo
-    /// let a_str = geo_service.format_location_a(&location);
+    /// ```ignore
     /// assert_eq!(
-    ///     a_str,
-    ///     "Mumbai,IN,19.076,72.8777"
+    ///     txt,
+    ///     "1.36 km - Mumbai (IN) - Pop: 12691836, TZ: Asia/Kolkata, Lat: 19.0728, Lon: 72.8826"
     /// );
     /// ```
-    fn format_location_a(&self, location: &Location) -> String {
+    fn format_nearest_txt(&self, location: &Location, distance: f64) -> String {
         format!(
-            "{},{},{},{}",
-            location.name, location.country, location.latitude, location.longitude
+            "{:.2} km - {}",
+            distance,
+            self.format_location_txt(location)
         )
     }
 
     /// Handles TXT record queries for geographic information.
     ///
-    /// Returns human-readable location data in TXT format.
+    /// Returns human-readable location data in TXT format. Coordinate queries
+    /// are answered with the nearest locations, closest first.
     ///
     /// # Arguments
+    /// * `query_name` - The DNS name being queried
     /// * `query` - The DNS query string
     ///
     /// # Returns
-    /// * `Option<Vec<Record>>` - TXT records with location data or None
-    async fn handle_txt_query(&self, query: &str) -> Option<Vec<Record>> {
-        let locations = self.geo.query(query)?;
+    /// * `Option<Answer>` - TXT records with location data or None
+    async fn handle_txt_query(&self, query_name: &Name, query: &str) -> Option<Answer> {
+        let mut response = ResponseBuilder::new(query_name, &self.ttls);
 
-        let mut records = Vec::new();
-        for location in locations {
-            let txt_data = self.format_location_txt(&location);
-            let record = Record::from_rdata(
-                Name::from_str(".").ok()?,
-                60,
-                RData::TXT(rdata::TXT::new(vec![txt_data])),
-            );
-            records.push(record);
+        if let Some((latitude, longitude)) = parse_coordinates(query) {
+            for (location, distance) in self.geo.nearest(latitude, longitude, self.nearest_results)
+            {
+                response.txt(&self.format_nearest_txt(location, distance));
+            }
+            return response.build();
         }
 
-        if records.is_empty() {
-            None
-        } else {
-            Some(records)
+        let locations = self.geo.query(query)?;
+
+        for location in locations {
+            response.txt(&self.format_location_txt(location));
         }
+        response.build()
     }
 
-    /// Handles A record queries for geographic information.
+    /// Handles LOC record queries for geographic information.
     ///
-    /// Returns location data in a structured format suitable for A records.
+    /// Returns one LOC record (RFC 1876) per matching location, most populous first, with
+    /// the location's elevation as the altitude.
     ///
     /// # Arguments
+    /// * `query_name` - The DNS name being queried
     /// * `query` - The DNS query string
     ///
     /// # Returns
-    /// * `Option<Vec<Record>>` - A records with location data or None
-    async fn handle_a_query(&self, query: &str) -> Option<Vec<Record>> {
+    /// * `Option<Answer>` - LOC records with the location coordinates or None
+    async fn handle_loc_query(&self, query_name: &Name, query: &str) -> Option<Answer> {
         let locations = self.geo.query(query)?;
 
-        let mut records = Vec::new();
+        let mut response = ResponseBuilder::new(query_name, &self.ttls);
         for location in locations {
-            let a_data = self.format_location_a(&location);
-            let record = Record::from_rdata(
-                Name::from_str(".").ok()?,
-                60,
-                RData::TXT(rdata::TXT::new(vec![a_data])),
+            response.loc(
+                location.latitude,
+                location.longitude,
+                f64::from(location.elevation),
             );
-            records.push(record);
-        }
-
-        if records.is_empty() {
-            None
-        } else {
-            Some(records)
         }
+        response.build()
     }
 }
 
@@ -389,13 +821,15 @@ impl Service for GeoService {
     async fn query(
         &self,
         _request: &Request,
-        _query_name: &Name,
+        query_name: &Name,
         query_type: RecordType,
         cleaned_query: &str,
-    ) -> Option<Vec<Record>> {
+    ) -> Option<Answer> {
         match query_type {
-            RecordType::TXT => self.handle_txt_query(cleaned_query).await,
-            RecordType::A => self.handle_a_query(cleaned_query).await,
+            RecordType::TXT => self.handle_txt_query(query_name, cleaned_query).await,
+            RecordType::Unknown(LOC_RECORD_TYPE) => {
+                self.handle_loc_query(query_name, cleaned_query).await
+            }
             _ => None,
         }
     }
@@ -410,3 +844,276 @@ impl Service for GeoService {
         Ok(summary.into_bytes())
     }
 }
+
+/// Builds locations for tests, with placeholder values for the fields a test leaves unset.
+#[cfg(test)]
+pub(crate) struct LocationBuilder(Location);
+
+#[cfg(test)]
+impl LocationBuilder {
+    /// Starts a UTC location at 0°, 0° with a population of 1000 in country `XX`.
+    pub(crate) fn new(id: &str, name: &str) -> Self {
+        Self(Location {
+            id: id.to_string(),
+            name: name.to_string(),
+            latitude: 0.0,
+            longitude: 0.0,
+            elevation: 0,
+            timezone_name: Arc::from(Tz::UTC.name()),
+            population: 1000,
+            timezone: Tz::UTC,
+            country: Arc::from("XX"),
+            admin1: Arc::from(""),
+            region: None,
+        })
+    }
+
+    pub(crate) fn coordinates(mut self, latitude: f64, longitude: f64) -> Self {
+        self.0.latitude = latitude;
+        self.0.longitude = longitude;
+        self
+    }
+
+    pub(crate) fn elevation(mut self, elevation: i32) -> Self {
+        self.0.elevation = elevation;
+        self
+    }
+
+    pub(crate) fn timezone(mut self, timezone: Tz) -> Self {
+        self.0.timezone_name = Arc::from(timezone.name());
+        self.0.timezone = timezone;
+        self
+    }
+
+    pub(crate) fn country(mut self, country: &str) -> Self {
+        self.0.country = Arc::from(country);
+        self
+    }
+
+    pub(crate) fn admin1(mut self, admin1: &str) -> Self {
+        self.0.admin1 = Arc::from(admin1);
+        self
+    }
+
+    pub(crate) fn region(mut self, region: Option<&str>) -> Self {
+        self.0.region = region.map(Arc::from);
+        self
+    }
+
+    pub(crate) fn build(self) -> Location {
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use tempfile::NamedTempFile;
+
+    /// A temporary file holding the given tab-separated lines.
+    fn data_file(lines: &[&str]) -> NamedTempFile {
+        let mut file = NamedTempFile::new().unwrap();
+        for line in lines {
+            writeln!(file, "{}", line).unwrap();
+        }
+        file
+    }
+
+    fn path(file: &NamedTempFile) -> &str {
+        file.path().to_str().unwrap()
+    }
+
+    fn location(id: &str, name: &str, country: &str, admin1: &str, timezone: Tz) -> Location {
+        LocationBuilder::new(id, name)
+            .timezone(timezone)
+            .country(country)
+            .admin1(admin1)
+            .build()
+    }
+
+    fn names_of<'a>(geo: &'a Geo, q: &str) -> Vec<&'a str> {
+        geo.query(q)
+            .unwrap_or_default()
+            .iter()
+            .map(|loc| loc.name.as_str())
+            .collect()
+    }
+
+    const SAO_PAULO: &str = "3448439\tSão Paulo\tSao Paulo\tSampa,San Paulo,Сан-Паулу\t-23.5475\t-46.63611\tP\tPPLA\tBR\t\t27\t3550308\t\t\t10021295\t\t761\tAmerica/Sao_Paulo\t2023-01-12";
+
+    #[test]
+    fn read_file_collects_native_and_inline_names() {
+        let file = data_file(&[SAO_PAULO]);
+
+        let (locations, names) = Geo::read_file(path(&file), false).unwrap();
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].name, "Sao Paulo");
+        assert_eq!(locations[0].elevation, 761);
+        assert_eq!(names["3448439"], ["São Paulo"]);
+
+        let (_, names) = Geo::read_file(path(&file), true).unwrap();
+        assert_eq!(
+            names["3448439"],
+            ["São Paulo", "Sampa", "San Paulo", "Сан-Паулу"]
+        );
+    }
+
+    #[test]
+    fn alternate_names_skip_pseudo_languages_and_filter_by_language() {
+        let file = data_file(&[
+            "1\t2867714\tde\tMünchen\t1\t\t\t\t\t",
+            "2\t2867714\ten\tMunich\t1\t\t\t\t\t",
+            "3\t2867714\tlink\thttps://en.wikipedia.org/wiki/Munich\t\t\t\t\t\t",
+            "4\t2867714\tpost\t80331\t\t\t\t\t\t",
+            "5\t2867714\t\tMuenchen\t\t\t\t\t\t",
+            "6\t2867714\tru\tМюнхен\t\t\t\t\t\t",
+            "7\t2950159\tde\tBerlin\t\t\t\t\t\t",
+        ]);
+        let ids = HashSet::from(["2867714"]);
+
+        let names = Geo::read_alternate_names(path(&file), &[], &ids).unwrap();
+        assert_eq!(names.len(), 1);
+        assert_eq!(
+            names["2867714"],
+            ["München", "Munich", "Muenchen", "Мюнхен"]
+        );
+
+        let languages = ["DE".to_string(), "ru".to_string()];
+        let names = Geo::read_alternate_names(path(&file), &languages, &ids).unwrap();
+        assert_eq!(names["2867714"], ["München", "Мюнхен"]);
+    }
+
+    #[test]
+    fn names_are_folded_and_idna_decoded() {
+        let mut geo = Geo::default();
+        let names = HashMap::from([(
+            "2867714".to_string(),
+            vec![
+                "München".to_string(),
+                "Muenchen".to_string(),
+                "Мюнхен".to_string(),
+            ],
+        )]);
+        geo.load(
+            vec![location(
+                "2867714",
+                "Munich",
+                "DE",
+                "02",
+                Tz::Europe__Berlin,
+            )],
+            &names,
+        );
+
+        for q in [
+            "munich",
+            "münchen",
+            "MÜNCHEN",
+            "munchen",
+            "muenchen",
+            "мюнхен",
+        ] {
+            assert_eq!(names_of(&geo, q), ["Munich"], "{}", q);
+        }
+        // Internationalized labels arrive punycode-encoded
+        assert_eq!(decode_idna("xn--mnchen-3ya"), "münchen");
+        assert_eq!(names_of(&geo, "xn--mnchen-3ya"), ["Munich"]);
+        assert_eq!(decode_idna("munich"), "munich");
+    }
+
+    #[test]
+    fn folding_keeps_non_latin_keys_unchanged() {
+        assert_eq!(fold_diacritics("münchen").as_deref(), Some("munchen"));
+        assert_eq!(fold_diacritics("munchen"), None);
+        assert_eq!(fold_diacritics("мюнхен"), None);
+    }
+
+    /// Springfields in Illinois, Missouri and Australia, with region names when given.
+    fn springfields(regions: bool) -> Geo {
+        let mut illinois = location("4250542", "Springfield", "US", "IL", Tz::America__Chicago);
+        illinois.population = 116_250;
+        let mut missouri = location("4409896", "Springfield", "US", "MO", Tz::America__Chicago);
+        missouri.population = 169_176;
+        let mut queensland = location(
+            "7839523",
+            "Springfield",
+            "AU",
+            "04",
+            Tz::Australia__Brisbane,
+        );
+        queensland.population = 20_000;
+        if regions {
+            illinois.region = Some(Arc::from("Illinois"));
+            missouri.region = Some(Arc::from("Missouri"));
+            queensland.region = Some(Arc::from("Queensland"));
+        }
+
+        let mut geo = Geo::default();
+        geo.load(vec![illinois, missouri, queensland], &AlternateNames::new());
+        geo
+    }
+
+    fn regions_of<'a>(geo: &'a Geo, q: &str) -> Vec<&'a str> {
+        geo.query(q)
+            .unwrap_or_default()
+            .iter()
+            .map(|loc| &*loc.admin1)
+            .collect()
+    }
+
+    #[test]
+    fn query_filters_by_country_and_region() {
+        let geo = springfields(true);
+
+        assert_eq!(regions_of(&geo, "springfield"), ["MO", "IL", "04"]);
+        assert_eq!(regions_of(&geo, "springfield/us"), ["MO", "IL"]);
+        assert_eq!(regions_of(&geo, "springfield/AU"), ["04"]);
+        assert_eq!(regions_of(&geo, "springfield/us/il"), ["IL"]);
+        assert_eq!(regions_of(&geo, "springfield/us/illinois"), ["IL"]);
+        assert_eq!(regions_of(&geo, "springfield/us/IL"), ["IL"]);
+        assert_eq!(regions_of(&geo, "springfield/au/queensland"), ["04"]);
+        assert!(geo.query("springfield/us/tx").is_none());
+        assert!(geo.query("springfield/gb").is_none());
+    }
+
+    #[test]
+    fn query_without_region_names_matches_admin1_codes_only() {
+        let geo = springfields(false);
+
+        assert_eq!(regions_of(&geo, "springfield/us/il"), ["IL"]);
+        assert_eq!(regions_of(&geo, "springfield/au/04"), ["04"]);
+        assert!(geo.query("springfield/us/illinois").is_none());
+
+        let results = geo.query("springfield/us/mo").unwrap();
+        assert_eq!(results[0].region_label(), Some("MO"));
+    }
+
+    #[test]
+    fn region_label_prefers_the_region_name() {
+        let mut location = location("1", "Nowhere", "XX", "", Tz::UTC);
+        assert_eq!(location.region_label(), None);
+
+        location.admin1 = Arc::from("IL");
+        assert_eq!(location.region_label(), Some("IL"));
+
+        location.region = Some(Arc::from("Illinois"));
+        assert_eq!(location.region_label(), Some("Illinois"));
+    }
+
+    #[test]
+    fn txt_answers_show_the_region_or_its_code() {
+        for (regions, place) in [(true, "US, Illinois"), (false, "US, IL")] {
+            let geo = Arc::new(springfields(regions));
+            let service = GeoService::new(Arc::clone(&geo), Ttls::new(GEO_TTL), 5, None);
+            let location = geo.query("springfield/us/il").unwrap()[0];
+            assert_eq!(
+                service.format_location_txt(location),
+                format!(
+                    "Springfield ({}) - Pop: 116250, TZ: America/Chicago, Lat: 0.0000, Lon: 0.0000",
+                    place
+                )
+            );
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! # Fuzzy Name Search
+//!
+//! Prefix and typo-tolerant matching over the cleaned location names of [`super::Geo`].
+//!
+//! Prefix matches come from a sorted list of keys. Typo candidates are gathered through
+//! a trigram index and then checked with the optimal string alignment distance
+//! (Levenshtein distance that also counts swapped neighbouring letters as one edit).
+
+use std::collections::HashMap;
+
+/// Queries shorter than this are only matched exactly
+const MIN_QUERY_LEN: usize = 3;
+
+/// Queries up to this length tolerate a single edit, longer ones two
+const SINGLE_EDIT_MAX_LEN: usize = 5;
+
+/// Index over location keys for prefix and fuzzy lookups.
+#[derive(Debug, Default)]
+pub struct SearchIndex {
+    /// All keys, sorted for prefix range lookups
+    keys: Vec<String>,
+    /// Maps each trigram to the indices of the keys containing it
+    trigrams: HashMap<[u8; 3], Vec<usize>>,
+}
+
+/// Splits a key into trigrams, padded so that short keys and word edges are covered.
+fn trigrams(key: &str) -> Vec<[u8; 3]> {
+    let padded: Vec<u8> = [b' ', b' ']
+        .into_iter()
+        .chain(key.bytes())
+        .chain([b' '])
+        .collect();
+    padded.windows(3).map(|w| [w[0], w[1], w[2]]).collect()
+}
+
+/// Computes the optimal string alignment distance between two byte strings.
+fn edit_distance(a: &[u8], b: &[u8]) -> usize {
+    let mut rows = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for (j, cell) in rows[0].iter_mut().enumerate() {
+        *cell = j;
+    }
+
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            let mut best = (rows[i - 1][j] + 1)
+                .min(rows[i][j - 1] + 1)
+                .min(rows[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                best = best.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = best;
+        }
+    }
+
+    rows[a.len()][b.len()]
+}
+
+impl SearchIndex {
+    /// Builds the index from the cleaned location keys.
+    ///
+    /// # Arguments
+    /// * `keys` - Iterator of unique location keys
+    pub fn build<'a>(keys: impl Iterator<Item = &'a String>) -> Self {
+        let mut keys: Vec<String> = keys.cloned().collect();
+        keys.sort_unstable();
+
+        let mut trigram_map: HashMap<[u8; 3], Vec<usize>> = HashMap::new();
+        for (index, key) in keys.iter().enumerate() {
+            for trigram in trigrams(key) {
+                let entries = trigram_map.entry(trigram).or_default();
+                // A key repeating a trigram is only listed once
+                if entries.last() != Some(&index) {
+                    entries.push(index);
+                }
+            }
+        }
+
+        Self {
+            keys,
+            trigrams: trigram_map,
+        }
+    }
+
+    /// Finds keys that start with the query or are within a small edit distance of it.
+    ///
+    /// # Arguments
+    /// * `query` - The cleaned query
+    ///
+    /// # Returns
+    /// * `Vec<(&str, f64)>` - Matching keys with their similarity in `(0, 1]`, where
+    ///   prefix matches score by how much of the key the query covers and typo matches by
+    ///   the share of characters that did not need an edit
+    pub fn matches(&self, query: &str) -> Vec<(&str, f64)> {
+        if query.len() < MIN_QUERY_LEN {
+            return Vec::new();
+        }
+
+        let mut found: HashMap<usize, f64> = HashMap::new();
+
+        let start = self.keys.partition_point(|key| key.as_str() < query);
+        for (index, key) in self.keys.iter().enumerate().skip(start) {
+            if !key.starts_with(query) {
+                break;
+            }
+            found.insert(index, query.len() as f64 / key.len() as f64);
+        }
+
+        let max_edits = if query.len() <= SINGLE_EDIT_MAX_LEN {
+            1
+        } else {
+            2
+        };
+        let mut query_trigrams = trigrams(query);
+        query_trigrams.sort_unstable();
+        query_trigrams.dedup();
+
+        // Each edit changes at most three trigrams (four for a swap of neighbouring
+        // letters), so a key within `max_edits` must share the remaining ones with the
+        // query (the q-gram lemma)
+        let min_shared = query_trigrams.len().saturating_sub(4 * max_edits).max(1);
+
+        // Only keys sharing a trigram get a counter, so misses stay cheap
+        let mut shared: HashMap<usize, u16> = HashMap::new();
+        for postings in query_trigrams.iter().filter_map(|t| self.trigrams.get(t)) {
+            for &index in postings {
+                if self.keys[index].len().abs_diff(query.len()) > max_edits {
+                    continue;
+                }
+                *shared.entry(index).or_default() += 1;
+            }
+        }
+
+        for (index, count) in shared {
+            if usize::from(count) < min_shared {
+                continue;
+            }
+
+            let key = &self.keys[index];
+            let edits = edit_distance(query.as_bytes(), key.as_bytes());
+            if edits <= max_edits {
+                let similarity = 1.0 - edits as f64 / query.len().max(key.len()) as f64;
+                let entry = found.entry(index).or_insert(similarity);
+                *entry = entry.max(similarity);
+            }
+        }
+
+        found
+            .into_iter()
+            .map(|(index, similarity)| (self.keys[index].as_str(), similarity))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn index(keys: &[&str]) -> SearchIndex {
+        let keys: Vec<String> = keys.iter().map(|key| key.to_string()).collect();
+        SearchIndex::build(keys.iter())
+    }
+
+    fn sorted(mut matches: Vec<(&str, f64)>) -> Vec<&str> {
+        matches.sort_by(|a, b| a.0.cmp(b.0));
+        matches.into_iter().map(|(key, _)| key).collect()
+    }
+
+    #[test]
+    fn edit_distance_counts_insertions_deletions_and_substitutions() {
+        assert_eq!(edit_distance(b"mumbai", b"mumbai"), 0);
+        assert_eq!(edit_distance(b"", b"pune"), 4);
+        assert_eq!(edit_distance(b"mumbai", b"mumbay"), 1);
+        assert_eq!(edit_distance(b"mumbai", b"mumbi"), 1);
+        assert_eq!(edit_distance(b"delhi", b"dehli"), 1);
+        assert_eq!(edit_distance(b"kitten", b"sitting"), 3);
+    }
+
+    #[test]
+    fn edit_distance_counts_a_swap_of_neighbours_as_one_edit() {
+        assert_eq!(edit_distance(b"chennai", b"chenani"), 1);
+        // Optimal string alignment never edits a substring twice
+        assert_eq!(edit_distance(b"ca", b"abc"), 3);
+    }
+
+    #[test]
+    fn matches_prefixes_and_typos() {
+        let index = index(&["bangalore", "bangkok", "mumbai", "mumbra", "delhi"]);
+
+        assert_eq!(sorted(index.matches("bang")), ["bangalore", "bangkok"]);
+        // Six letters tolerate two edits, enough to reach "mumbra" as well
+        assert_eq!(sorted(index.matches("mumbay")), ["mumbai", "mumbra"]);
+        assert_eq!(sorted(index.matches("mumbi")), ["mumbai"]);
+        assert_eq!(sorted(index.matches("dehli")), ["delhi"]);
+        assert!(index.matches("zurich").is_empty());
+    }
+
+    #[test]
+    fn matches_scores_by_similarity() {
+        let index = index(&["mumbai"]);
+
+        let matches = index.matches("mumba");
+        assert_eq!(matches.len(), 1);
+        // The prefix match covers 5 of 6 letters, which beats one edit in six
+        assert!((matches[0].1 - 5.0 / 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn matches_ignores_short_queries() {
+        let index = index(&["ny", "nyc"]);
+        assert!(index.matches("ny").is_empty());
+    }
+}
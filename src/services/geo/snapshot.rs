@@ -0,0 +1,420 @@
+//! # Geo Snapshot
+//!
+//! A compact binary snapshot of the built [`Geo`] index, so the server can start without
+//! re-parsing the geonames TSV files.
+//!
+//! ## Layout
+//! All integers and floats are little-endian; strings are a `u32` byte length followed by UTF-8.
+//!
+//! | Field        | Type      | Description                                        |
+//! |--------------|-----------|----------------------------------------------------|
+//! | magic        | `[u8; 8]` | `RDNSGEO\0`                                        |
+//! | version      | `u32`     | [`SNAPSHOT_VERSION`]                               |
+//! | checksum     | `u32`     | CRC-32 of the payload                              |
+//! | payload size | `u64`     | Length of the payload in bytes                     |
+//! | fingerprint  | string    | Sizes and modification times of the source files   |
+//! | locations    | list      | Every [`Location`], in load order                  |
+//! | keys         | list      | Every lookup key with the indices of its locations |
+//!
+//! The prefix/fuzzy and spatial indexes are rebuilt from the keys and locations on load.
+
+use super::search::SearchIndex;
+use super::spatial::KdTree;
+use super::{Geo, Interner, Location, NameSources};
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use chrono_tz::Tz;
+use memmap2::Mmap;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::str::FromStr;
+use std::time::UNIX_EPOCH;
+
+/// Identifies a geo snapshot file
+const MAGIC: &[u8; 8] = b"RDNSGEO\0";
+
+/// Format version, bumped whenever the layout or the meaning of a field changes
+pub const SNAPSHOT_VERSION: u32 = 2;
+
+/// Magic, version, checksum and payload size
+const HEADER_LEN: usize = 8 + 4 + 4 + 8;
+
+/// Describes the inputs a snapshot was built from.
+///
+/// A snapshot is stale as soon as a source file changes size or modification time, or the
+/// name options change.
+///
+/// # Arguments
+/// * `file_path` - Path to the geonames.org cities data file
+/// * `sources` - Where alternate and region names are read from
+///
+/// # Returns
+/// * `Result<String>` - The fingerprint, or an error if a source file cannot be inspected
+pub fn fingerprint(file_path: &str, sources: &NameSources) -> Result<String> {
+    let describe = |path: &str| -> Result<String> {
+        let metadata =
+            fs::metadata(path).with_context(|| format!("Failed to inspect '{}'", path))?;
+        let modified = metadata
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        Ok(format!("{}:{}:{}", path, metadata.len(), modified))
+    };
+
+    let mut parts = vec![describe(file_path)?];
+    if let Some(path) = &sources.alternate_names_path {
+        parts.push(describe(path)?);
+    }
+    if let Some(path) = &sources.admin1_path {
+        parts.push(describe(path)?);
+    }
+    parts.push(format!("inline={}", sources.inline));
+    parts.push(format!("languages={}", sources.languages.join(",")));
+
+    Ok(parts.join("|"))
+}
+
+/// Appends snapshot fields to a byte buffer.
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn u8(&mut self, value: u8) {
+        self.buf.push(value);
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn i32(&mut self, value: i32) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn f64(&mut self, value: f64) {
+        self.buf.extend_from_slice(&value.to_le_bytes());
+    }
+
+    fn str(&mut self, value: &str) {
+        self.u32(value.len() as u32);
+        self.buf.extend_from_slice(value.as_bytes());
+    }
+}
+
+/// Reads snapshot fields from a byte slice, failing on truncated data.
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| anyhow!("Snapshot is truncated"))?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into()?)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn i32(&mut self) -> Result<i32> {
+        Ok(i32::from_le_bytes(self.array()?))
+    }
+
+    fn f64(&mut self) -> Result<f64> {
+        Ok(f64::from_le_bytes(self.array()?))
+    }
+
+    fn str(&mut self) -> Result<&'a str> {
+        let len = self.u32()? as usize;
+        Ok(std::str::from_utf8(self.take(len)?)?)
+    }
+}
+
+/// Writes a snapshot of the index.
+///
+/// The snapshot is written to a temporary file first and renamed into place, so a
+/// server starting at the same time never maps a half-written file, and a file that is
+/// already mapped keeps its contents.
+///
+/// # Arguments
+/// * `geo` - The built index
+/// * `path` - Destination of the snapshot
+/// * `fingerprint` - Fingerprint of the source files, see [`fingerprint`]
+pub fn write(geo: &Geo, path: &str, fingerprint: &str) -> Result<()> {
+    let mut payload = Writer::default();
+    payload.str(fingerprint);
+
+    payload.u32(geo.locations.len() as u32);
+    for location in &geo.locations {
+        payload.str(&location.id);
+        payload.str(&location.name);
+        payload.f64(location.latitude);
+        payload.f64(location.longitude);
+        payload.i32(location.elevation);
+        payload.str(&location.timezone_name);
+        payload.u64(location.population);
+        payload.str(&location.country);
+        payload.str(&location.admin1);
+        match &location.region {
+            Some(region) => {
+                payload.u8(1);
+                payload.str(region);
+            }
+            None => payload.u8(0),
+        }
+    }
+
+    payload.u32(geo.tz_map.len() as u32);
+    for (key, indices) in &geo.tz_map {
+        payload.str(key);
+        payload.u32(indices.len() as u32);
+        for &index in indices {
+            payload.u32(index);
+        }
+    }
+
+    let mut file = Writer::default();
+    file.buf.extend_from_slice(MAGIC);
+    file.u32(SNAPSHOT_VERSION);
+    file.u32(crc32fast::hash(&payload.buf));
+    file.u64(payload.buf.len() as u64);
+    file.buf.extend_from_slice(&payload.buf);
+
+    let tmp_path = format!("{}.tmp", path);
+    fs::write(&tmp_path, &file.buf)
+        .with_context(|| format!("Failed to write snapshot '{}'", tmp_path))?;
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("Failed to move snapshot into place at '{}'", path))?;
+    Ok(())
+}
+
+/// Loads an index from a snapshot.
+///
+/// # Arguments
+/// * `path` - Path of the snapshot
+/// * `fingerprint` - Expected fingerprint of the source files, see [`fingerprint`]
+///
+/// # Returns
+/// * `Ok(Geo)` - The loaded index
+/// * `Err(anyhow::Error)` - If the snapshot is missing, corrupt, from another format
+///   version or stale
+pub fn read(path: &str, fingerprint: &str) -> Result<Geo> {
+    let file = File::open(path).with_context(|| format!("Failed to open snapshot '{}'", path))?;
+    // SAFETY: the mapping is only read within this function, and everything kept is copied
+    // out of it. `write` replaces snapshots by renaming a new file over the old one, which
+    // leaves a mapped file untouched; the file must not be modified in place by other means.
+    let bytes = unsafe { Mmap::map(&file) }
+        .with_context(|| format!("Failed to map snapshot '{}'", path))?;
+
+    let mut header = Reader {
+        data: &bytes,
+        pos: 0,
+    };
+    ensure!(
+        bytes.len() >= HEADER_LEN && header.take(MAGIC.len())? == MAGIC,
+        "Not a geo snapshot"
+    );
+    let version = header.u32()?;
+    ensure!(
+        version == SNAPSHOT_VERSION,
+        "Snapshot version {} is not supported (expected {})",
+        version,
+        SNAPSHOT_VERSION
+    );
+    let checksum = header.u32()?;
+    let payload_len = header.u64()?;
+    let payload = &bytes[HEADER_LEN..];
+    ensure!(payload.len() as u64 == payload_len, "Snapshot is truncated");
+    ensure!(
+        crc32fast::hash(payload) == checksum,
+        "Snapshot checksum mismatch"
+    );
+
+    let mut reader = Reader {
+        data: payload,
+        pos: 0,
+    };
+    if reader.str()? != fingerprint {
+        bail!("Snapshot is stale, the source files have changed");
+    }
+
+    let location_count = reader.u32()? as usize;
+    let mut locations = Vec::with_capacity(location_count);
+    let mut interner = Interner::default();
+    for _ in 0..location_count {
+        let id = reader.str()?.to_string();
+        let name = reader.str()?.to_string();
+        let latitude = reader.f64()?;
+        let longitude = reader.f64()?;
+        let elevation = reader.i32()?;
+        let timezone_name = interner.intern(reader.str()?);
+        let population = reader.u64()?;
+        let country = interner.intern(reader.str()?);
+        let admin1 = interner.intern(reader.str()?);
+        let region = match reader.u8()? {
+            0 => None,
+            _ => Some(interner.intern(reader.str()?)),
+        };
+        let timezone = Tz::from_str(&timezone_name)
+            .map_err(|_| anyhow!("Unknown timezone '{}' in snapshot", timezone_name))?;
+
+        locations.push(Location {
+            id,
+            name,
+            latitude,
+            longitude,
+            elevation,
+            timezone_name,
+            population,
+            timezone,
+            country,
+            admin1,
+            region,
+        });
+    }
+
+    let key_count = reader.u32()? as usize;
+    let mut tz_map = HashMap::with_capacity(key_count);
+    for _ in 0..key_count {
+        let key = reader.str()?.to_string();
+        let len = reader.u32()? as usize;
+        let mut indices = Vec::with_capacity(len);
+        for _ in 0..len {
+            let index = reader.u32()?;
+            ensure!(
+                (index as usize) < locations.len(),
+                "Snapshot references unknown location {}",
+                index
+            );
+            indices.push(index);
+        }
+        tz_map.insert(key, indices);
+    }
+
+    Ok(Geo {
+        search: SearchIndex::build(tz_map.keys()),
+        spatial: KdTree::build(locations.iter().map(|loc| (loc.latitude, loc.longitude))),
+        count: locations.len(),
+        tz_map,
+        locations,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::geo::LocationBuilder;
+    use tempfile::TempDir;
+
+    fn location(
+        id: &str,
+        name: &str,
+        latitude: f64,
+        longitude: f64,
+        region: Option<&str>,
+    ) -> Location {
+        LocationBuilder::new(id, name)
+            .coordinates(latitude, longitude)
+            .elevation(14)
+            .timezone(Tz::Asia__Kolkata)
+            .country("IN")
+            .admin1("16")
+            .region(region)
+            .build()
+    }
+
+    fn geo() -> Geo {
+        let mut geo = Geo::default();
+        geo.load(
+            vec![
+                location("1275339", "Mumbai", 19.07283, 72.88261, Some("Maharashtra")),
+                location("1259229", "Pune", 18.51957, 73.85535, None),
+            ],
+            &HashMap::from([("1275339".to_string(), vec!["Bombay".to_string()])]),
+        );
+        geo
+    }
+
+    /// The snapshot path inside a temporary directory.
+    fn snapshot_path(dir: &TempDir) -> String {
+        dir.path()
+            .join("geo.snapshot")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    #[test]
+    fn snapshot_round_trip_keeps_locations_and_keys() {
+        let dir = TempDir::new().unwrap();
+        let path = snapshot_path(&dir);
+        let original = geo();
+        write(&original, &path, "fingerprint").unwrap();
+        let loaded = read(&path, "fingerprint").unwrap();
+
+        assert_eq!(loaded.count, original.count);
+        assert_eq!(loaded.tz_map, original.tz_map);
+        for (a, b) in loaded.locations.iter().zip(&original.locations) {
+            assert_eq!(a.id, b.id);
+            assert_eq!(a.name, b.name);
+            assert_eq!(a.latitude, b.latitude);
+            assert_eq!(a.longitude, b.longitude);
+            assert_eq!(a.elevation, b.elevation);
+            assert_eq!(a.timezone, b.timezone);
+            assert_eq!(a.population, b.population);
+            assert_eq!(a.country, b.country);
+            assert_eq!(a.admin1, b.admin1);
+            assert_eq!(a.region, b.region);
+        }
+
+        // The rebuilt indexes answer like the original ones
+        assert_eq!(loaded.query("bombay").unwrap()[0].name, "Mumbai");
+        assert_eq!(loaded.nearest(18.5, 73.8, 1)[0].0.name, "Pune");
+    }
+
+    #[test]
+    fn snapshot_rejects_stale_and_corrupt_files() {
+        let dir = TempDir::new().unwrap();
+        let path = snapshot_path(&dir);
+        write(&geo(), &path, "fingerprint").unwrap();
+
+        let err = read(&path, "other").unwrap_err();
+        assert!(err.to_string().contains("stale"), "{:#}", err);
+
+        let mut bytes = fs::read(&path).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        fs::write(&path, &bytes).unwrap();
+        let err = read(&path, "fingerprint").unwrap_err();
+        assert!(err.to_string().contains("checksum"), "{:#}", err);
+
+        bytes[8..12].copy_from_slice(&(SNAPSHOT_VERSION + 1).to_le_bytes());
+        fs::write(&path, &bytes).unwrap();
+        let err = read(&path, "fingerprint").unwrap_err();
+        assert!(err.to_string().contains("version"), "{:#}", err);
+    }
+}
@@ -0,0 +1,183 @@
+//! # Spatial Index
+//!
+//! A static k-d tree over locations, used for reverse geocoding (coordinate to nearest cities).
+//!
+//! Coordinates are projected onto the unit sphere as 3D vectors, so the straight-line
+//! (chord) distance between two points grows monotonically with their great-circle
+//! distance. This avoids special cases at the poles and the antimeridian.
+
+/// A point on the unit sphere and the index of its location in [`super::Geo`].
+type Point = ([f64; 3], usize);
+
+/// Number of dimensions of the projected points
+const DIMENSIONS: usize = 3;
+
+/// Static k-d tree over projected coordinates.
+///
+/// The tree is stored implicitly: every sub-slice of `points` keeps its median at the
+/// middle index, with the smaller half before it and the larger half after it.
+#[derive(Debug, Default)]
+pub struct KdTree {
+    points: Vec<Point>,
+}
+
+/// Projects a latitude/longitude pair in decimal degrees onto the unit sphere.
+fn to_unit_vector(latitude: f64, longitude: f64) -> [f64; 3] {
+    let (sin_lat, cos_lat) = latitude.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = longitude.to_radians().sin_cos();
+    [cos_lat * cos_lon, cos_lat * sin_lon, sin_lat]
+}
+
+/// Squared euclidean distance between two projected points.
+fn squared_distance(a: &[f64; 3], b: &[f64; 3]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum()
+}
+
+impl KdTree {
+    /// Builds a tree from the coordinates of a list of locations.
+    ///
+    /// # Arguments
+    /// * `coordinates` - Iterator of (latitude, longitude) pairs; the position in the
+    ///   iterator is the index returned by [`KdTree::nearest`]
+    pub fn build(coordinates: impl Iterator<Item = (f64, f64)>) -> Self {
+        let mut points: Vec<Point> = coordinates
+            .enumerate()
+            .map(|(index, (lat, lon))| (to_unit_vector(lat, lon), index))
+            .collect();
+
+        Self::partition(&mut points, 0);
+        Self { points }
+    }
+
+    /// Recursively moves the median of each sub-slice to its middle.
+    fn partition(points: &mut [Point], depth: usize) {
+        if points.len() <= 1 {
+            return;
+        }
+
+        let axis = depth % DIMENSIONS;
+        let mid = points.len() / 2;
+        points.select_nth_unstable_by(mid, |a, b| a.0[axis].total_cmp(&b.0[axis]));
+
+        let (left, right) = points.split_at_mut(mid);
+        Self::partition(left, depth + 1);
+        Self::partition(&mut right[1..], depth + 1);
+    }
+
+    /// Finds the locations closest to a coordinate.
+    ///
+    /// # Arguments
+    /// * `latitude` - Latitude in decimal degrees
+    /// * `longitude` - Longitude in decimal degrees
+    /// * `k` - Maximum number of results
+    ///
+    /// # Returns
+    /// * `Vec<usize>` - Location indices, closest first
+    pub fn nearest(&self, latitude: f64, longitude: f64, k: usize) -> Vec<usize> {
+        if k == 0 {
+            return Vec::new();
+        }
+
+        let target = to_unit_vector(latitude, longitude);
+        let mut best: Vec<(f64, usize)> = Vec::with_capacity(k + 1);
+        Self::search(&self.points, 0, &target, k, &mut best);
+        best.into_iter().map(|(_, index)| index).collect()
+    }
+
+    /// Depth-first search keeping the `k` closest points seen so far in `best`, sorted by distance.
+    fn search(
+        points: &[Point],
+        depth: usize,
+        target: &[f64; 3],
+        k: usize,
+        best: &mut Vec<(f64, usize)>,
+    ) {
+        if points.is_empty() {
+            return;
+        }
+
+        let axis = depth % DIMENSIONS;
+        let mid = points.len() / 2;
+        let (coordinates, index) = &points[mid];
+
+        let distance = squared_distance(coordinates, target);
+        if best.len() < k || distance < best[best.len() - 1].0 {
+            let position = best.partition_point(|(d, _)| *d <= distance);
+            best.insert(position, (distance, *index));
+            best.truncate(k);
+        }
+
+        let diff = target[axis] - coordinates[axis];
+        let (near, far) = if diff < 0.0 {
+            (&points[..mid], &points[mid + 1..])
+        } else {
+            (&points[mid + 1..], &points[..mid])
+        };
+
+        Self::search(near, depth + 1, target, k, best);
+        // The far side can only hold closer points if the splitting plane is within reach
+        if best.len() < k || diff.powi(2) < best[best.len() - 1].0 {
+            Self::search(far, depth + 1, target, k, best);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Indices of the `k` points closest to a coordinate, by exhaustive search.
+    fn brute_force(
+        coordinates: &[(f64, f64)],
+        latitude: f64,
+        longitude: f64,
+        k: usize,
+    ) -> Vec<usize> {
+        let target = to_unit_vector(latitude, longitude);
+        let mut indices: Vec<usize> = (0..coordinates.len()).collect();
+        indices.sort_by(|a, b| {
+            let (a_lat, a_lon) = coordinates[*a];
+            let (b_lat, b_lon) = coordinates[*b];
+            squared_distance(&to_unit_vector(a_lat, a_lon), &target)
+                .total_cmp(&squared_distance(&to_unit_vector(b_lat, b_lon), &target))
+        });
+        indices.truncate(k);
+        indices
+    }
+
+    #[test]
+    fn nearest_matches_brute_force() {
+        // A deterministic scatter of points over the whole globe
+        let coordinates: Vec<(f64, f64)> = (0..500)
+            .map(|i| {
+                let i = i as f64;
+                ((i * 37.3) % 180.0 - 90.0, (i * 91.7) % 360.0 - 180.0)
+            })
+            .collect();
+        let tree = KdTree::build(coordinates.iter().copied());
+
+        for (latitude, longitude) in [(0.0, 0.0), (19.07, 72.88), (-33.87, 151.21), (89.9, 10.0)] {
+            assert_eq!(
+                tree.nearest(latitude, longitude, 5),
+                brute_force(&coordinates, latitude, longitude, 5)
+            );
+        }
+    }
+
+    #[test]
+    fn nearest_wraps_around_the_antimeridian() {
+        let coordinates = [(0.0, 179.5), (0.0, 170.0), (0.0, -175.0)];
+        let tree = KdTree::build(coordinates.into_iter());
+
+        // Just west of the antimeridian, the point across it beats the one 10 degrees away
+        assert_eq!(tree.nearest(0.0, -179.9, 3), [0, 2, 1]);
+    }
+
+    #[test]
+    fn nearest_handles_small_and_empty_inputs() {
+        let tree = KdTree::build([(10.0, 10.0), (20.0, 20.0)].into_iter());
+        assert_eq!(tree.nearest(19.0, 19.0, 5), [1, 0]);
+        assert!(tree.nearest(19.0, 19.0, 0).is_empty());
+        assert!(KdTree::default().nearest(0.0, 0.0, 3).is_empty());
+    }
+}
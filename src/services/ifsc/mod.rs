@@ -0,0 +1,422 @@
+//! # IFSC DNS Service
+//!
+//! This module exposes the Indian bank branch data indexed by [`crate::ifsc::IFSC`]
+//! through DNS queries, looking branches up by their IFSC code, their MICR code, or
+//! their bank and city.
+
+use crate::handlers::{Answer, DnsHandlers, ResponseBuilder, Service, Ttls};
+use crate::ifsc::{Branch, IFSC, PAYMENT_RAILS, is_micr};
+use anyhow::Result;
+use async_trait::async_trait;
+use hickory_proto::rr::{Name, RecordType};
+use hickory_server::server::Request;
+use std::sync::Arc;
+
+/// Default TTL of IFSC answers, branch details rarely change
+pub const IFSC_TTL: u32 = 3600;
+
+/// Default maximum number of branches listed for a MICR or bank and city query
+pub const IFSC_MAX_RESULTS: usize = 10;
+
+/// Which lookups an [`IfscService`] answers, depending on the suffix it is registered under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IfscMode {
+    /// IFSC code and bank-city lookups (`ifsc` suffix)
+    Ifsc,
+    /// MICR code lookups (`micr` suffix)
+    Micr,
+}
+
+/// Returns a field of the dataset, or "n/a" when it is empty.
+fn or_na(value: &str) -> &str {
+    if value.is_empty() { "n/a" } else { value }
+}
+
+/// DNS service returning the details of an Indian bank branch.
+///
+/// Branches are looked up by their 11 character IFSC code, case-insensitively
+/// (e.g., `dig sbin0000001.ifsc`). The answer contains one TXT record each for the
+/// bank, branch, address, city, district, state, MICR code, supported payment rails,
+/// SWIFT code and contact number.
+///
+/// Branches can also be listed by bank code and city or district
+/// (e.g., `dig sbin-pune.ifsc` or `dig hdfc-navi-mumbai.ifsc`), one TXT record per
+/// branch, capped at the configured maximum. Registered in [`IfscMode::Micr`], the
+/// service instead lists the branches with a 9 digit MICR code printed on cheques
+/// (e.g., `dig 400002001.micr`).
+///
+/// Malformed and unknown codes are answered with an `error:` TXT record explaining the
+/// problem; for unknown codes it names the bank of the 4-letter prefix when known.
+pub struct IfscService {
+    ifsc: Arc<IFSC>,
+    mode: IfscMode,
+    ttls: Ttls,
+    max_results: usize,
+}
+
+impl IfscService {
+    /// Creates a new IfscService instance.
+    ///
+    /// # Arguments
+    /// * `ifsc` - The loaded IFSC index, shared between the ifsc, micr and bank suffixes
+    /// * `mode` - Which lookups to answer
+    /// * `ttls` - TTLs of the returned records, per record type
+    /// * `max_results` - Maximum number of branches listed for a MICR or bank and city query
+    pub fn new(ifsc: Arc<IFSC>, mode: IfscMode, ttls: Ttls, max_results: usize) -> Self {
+        Self {
+            ifsc,
+            mode,
+            ttls,
+            max_results: max_results.max(1),
+        }
+    }
+
+    /// Formats a branch as human-readable lines, one per TXT record.
+    ///
+    /// # Example
+    /// ```ignore
+    /// assert_eq!(
+    ///     lines,
+    ///     [
+    ///         "IFSC: SBIN0000001",
+    ///         "Bank: State Bank of India",
+    ///         "Branch: KOLKATA MAIN",
+    ///         "Address: SAMRIDDHI BHAWAN, 1 STRAND ROAD, KOLKATA 700 001",
+    ///         "City: KOLKATA",
+    ///         "District: KOLKATA",
+    ///         "State: WEST BENGAL",
+    ///         "MICR: 700002021",
+    ///         "Payments: UPI, IMPS, NEFT, RTGS, SWIFT",
+    ///         "SWIFT: SBININBBXXX",
+    ///         "Contact: +913322622222",
+    ///     ]
+    /// );
+    /// ```
+    fn format_branch(branch: &Branch) -> Vec<String> {
+        let payments = match branch.payment_rails() {
+            Some(rails) => {
+                let supported: Vec<_> = PAYMENT_RAILS
+                    .iter()
+                    .zip(rails)
+                    .filter(|(_, supported)| *supported)
+                    .map(|(rail, _)| *rail)
+                    .collect();
+                if supported.is_empty() {
+                    "none".to_string()
+                } else {
+                    supported.join(", ")
+                }
+            }
+            None => "n/a".to_string(),
+        };
+
+        vec![
+            format!("IFSC: {}", branch.ifsc),
+            format!("Bank: {}", branch.bank),
+            format!("Branch: {}", branch.branch),
+            format!("Address: {}", branch.address),
+            format!("City: {}", branch.city),
+            format!("District: {}", branch.district),
+            format!("State: {}", branch.state),
+            format!("MICR: {}", or_na(&branch.micr)),
+            format!("Payments: {}", payments),
+            format!("SWIFT: {}", or_na(&branch.swift)),
+            format!("Contact: {}", or_na(&branch.contact)),
+        ]
+    }
+
+    /// Formats a branch as a single line, for answers listing several branches.
+    ///
+    /// # Example
+    /// ```ignore
+    /// assert_eq!(
+    ///     line,
+    ///     "SBIN0000454 - State Bank of India, PUNE MAIN, LAXMI ROAD, PUNE 411002 (MICR 411002002)"
+    /// );
+    /// ```
+    fn format_branch_line(branch: &Branch) -> String {
+        let mut line = format!(
+            "{} - {}, {}, {}",
+            branch.ifsc, branch.bank, branch.branch, branch.address
+        );
+        if !branch.micr.is_empty() {
+            line.push_str(&format!(" (MICR {})", branch.micr));
+        }
+        line
+    }
+
+    /// Lists branches, one TXT record each, up to `max_results`.
+    ///
+    /// When branches are left out, a final record tells how many were shown of how many.
+    ///
+    /// # Arguments
+    /// * `query_name` - The DNS name being queried
+    /// * `branches` - The matching branches, in answer order
+    /// * `not_found` - Error message returned when there are no branches
+    ///
+    /// # Returns
+    /// * `Option<Answer>` - TXT records listing the branches, or an error record
+    fn list_branches(
+        &self,
+        query_name: &Name,
+        branches: &[&Branch],
+        not_found: &str,
+    ) -> Option<Answer> {
+        if branches.is_empty() {
+            return Some(DnsHandlers::create_error_response(query_name, not_found));
+        }
+
+        let mut response = ResponseBuilder::new(query_name, &self.ttls);
+        for branch in branches.iter().take(self.max_results) {
+            response.txt(&Self::format_branch_line(branch));
+        }
+        if branches.len() > self.max_results {
+            response.txt(&format!(
+                "{} of {} branches shown",
+                self.max_results,
+                branches.len()
+            ));
+        }
+        response.build()
+    }
+
+    /// Handles "bank-city" queries, listing the branches of a bank in a city or district.
+    fn handle_place_query(&self, query_name: &Name, query: &str) -> Option<Answer> {
+        let (bank_code, place) = query.split_once('-')?;
+        let branches = self.ifsc.find_by_place(bank_code, place);
+
+        let not_found = match self.ifsc.bank_name(bank_code) {
+            Some(bank) => format!("no {} branches found in {}", bank, place),
+            None => format!("unknown bank code {}", bank_code.to_ascii_uppercase()),
+        };
+        self.list_branches(query_name, &branches, &not_found)
+    }
+
+    /// Handles MICR code queries, listing the branches sharing the code.
+    fn handle_micr_query(&self, query_name: &Name, micr: &str) -> Option<Answer> {
+        if !is_micr(micr) {
+            return Some(DnsHandlers::create_error_response(
+                query_name,
+                &format!("malformed MICR code '{}': must be 9 digits", micr),
+            ));
+        }
+
+        let branches = self.ifsc.find_by_micr(micr);
+        self.list_branches(
+            query_name,
+            &branches,
+            &format!("unknown MICR code {}", micr),
+        )
+    }
+}
+
+#[async_trait]
+impl Service for IfscService {
+    /// Handles IFSC code and bank-city queries, or MICR code queries in [`IfscMode::Micr`].
+    ///
+    /// ## Arguments
+    /// * `request` - The DNS request
+    /// * `query_name` - The DNS name being queried
+    /// * `query_type` - The type of DNS record requested (only `TXT` is supported)
+    /// * `cleaned_query` - The cleaned query string (e.g., "sbin0000001", "400002001"
+    ///   or "sbin-pune")
+    ///
+    /// ## Returns
+    /// * `Some(Answer)` - TXT records describing the branch or listing the matching
+    ///   branches, or an error record if the code is malformed or nothing matches
+    /// * `None` - If the query type is not supported
+    async fn query(
+        &self,
+        _request: &Request,
+        query_name: &Name,
+        query_type: RecordType,
+        cleaned_query: &str,
+    ) -> Option<Answer> {
+        if query_type != RecordType::TXT {
+            return None;
+        }
+
+        if self.mode == IfscMode::Micr {
+            return self.handle_micr_query(query_name, cleaned_query);
+        }
+        if cleaned_query.contains('-') {
+            return self.handle_place_query(query_name, cleaned_query);
+        }
+
+        let branch = match self.ifsc.lookup(cleaned_query) {
+            Ok(branch) => branch,
+            Err(error) => {
+                return Some(DnsHandlers::create_error_response(
+                    query_name,
+                    &error.to_string(),
+                ));
+            }
+        };
+
+        let mut response = ResponseBuilder::new(query_name, &self.ttls);
+        for line in Self::format_branch(branch) {
+            response.txt(&line);
+        }
+        response.build()
+    }
+
+    /// Dumps service statistics for debugging purposes.
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>>` - Service summary as bytes
+    async fn dump(&self) -> Result<Vec<u8>> {
+        let report = self.ifsc.load_report();
+        let mut summary = format!(
+            "IFSC service with {} branches from {} bank files ({} skipped, {} ignored), loaded in {:.2?}",
+            self.ifsc.count(),
+            report.banks.len(),
+            report.skipped.len(),
+            report.ignored,
+            report.elapsed
+        );
+        for stats in &report.banks {
+            summary.push_str(&format!(
+                "\n{}: {} branches in {:.2?}",
+                stats.bank_code, stats.branches, stats.elapsed
+            ));
+        }
+        for (source, reason) in &report.skipped {
+            summary.push_str(&format!("\nskipped {}: {}", source, reason));
+        }
+        Ok(summary.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::test_request;
+    use crate::ifsc::fixtures::{GOOD_BANK, KKBK_BANK, SBIN_BANK, load};
+    use std::str::FromStr;
+
+    fn service() -> IfscService {
+        let ifsc = Arc::new(load(&[("HDFC.json", GOOD_BANK)]));
+        IfscService::new(ifsc, IfscMode::Ifsc, Ttls::new(IFSC_TTL), IFSC_MAX_RESULTS)
+    }
+
+    /// Answers a TXT query, returning the text of each record.
+    async fn answer(service: &IfscService, query: &str) -> Vec<String> {
+        let name = Name::from_str(&format!("{}.ifsc.localhost.", query)).unwrap();
+        let request = test_request(&name, RecordType::TXT, None);
+
+        service
+            .query(&request, &name, RecordType::TXT, query)
+            .await
+            .unwrap()
+            .records
+            .iter()
+            .map(|record| record.data().to_string())
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn ifsc_codes_are_answered_with_the_branch_details() {
+        assert_eq!(
+            answer(&service(), "hdfc0000001").await,
+            [
+                "IFSC: HDFC0000001",
+                "Bank: HDFC Bank",
+                "Branch: SANDOZ HOUSE",
+                "Address: SANDOZ HOUSE, WORLI, MUMBAI 400018",
+                "City: MUMBAI",
+                "District: MUMBAI",
+                "State: MAHARASHTRA",
+                "MICR: 400240015",
+                "Payments: n/a",
+                "SWIFT: n/a",
+                "Contact: n/a",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn ifsc_codes_are_case_insensitive() {
+        let service = service();
+        let expected = answer(&service, "hdfc0000001").await;
+        assert_eq!(answer(&service, "HDFC0000001").await, expected);
+        assert_eq!(answer(&service, "HdFc0000001").await, expected);
+    }
+
+    #[tokio::test]
+    async fn malformed_and_unknown_codes_are_explained() {
+        let service = service();
+        assert_eq!(
+            answer(&service, "hdfc000001").await,
+            ["error: malformed IFSC code 'hdfc000001': must be 11 characters long"]
+        );
+        assert_eq!(
+            answer(&service, "hdfc0000002").await,
+            ["error: unknown IFSC code HDFC0000002 (bank: HDFC Bank)"]
+        );
+        assert_eq!(
+            answer(&service, "abcd0000001").await,
+            ["error: unknown IFSC code ABCD0000001 (unknown bank ABCD)"]
+        );
+    }
+
+    #[tokio::test]
+    async fn branch_lists_are_capped() {
+        let ifsc = Arc::new(load(&[("SBIN.json", SBIN_BANK)]));
+
+        let service = IfscService::new(Arc::clone(&ifsc), IfscMode::Ifsc, Ttls::new(60), 2);
+        assert_eq!(
+            answer(&service, "sbin-pune").await,
+            [
+                "SBIN0000454 - State Bank of India, PUNE MAIN, LAXMI ROAD, PUNE 411002 (MICR 411002002)",
+                "SBIN0001110 - State Bank of India, SHIVAJINAGAR, SHIVAJINAGAR, PUNE 411005 (MICR 411002003)",
+                "2 of 3 branches shown",
+            ]
+        );
+        assert_eq!(
+            answer(&service, "sbin-thane").await,
+            ["SBIN0003333 - State Bank of India, VASHI, SECTOR 17, VASHI, NAVI MUMBAI 400703"]
+        );
+        assert_eq!(
+            answer(&service, "sbin-nagpur").await,
+            ["error: no State Bank of India branches found in nagpur"]
+        );
+
+        let service = IfscService::new(ifsc, IfscMode::Micr, Ttls::new(60), 1);
+        assert_eq!(
+            answer(&service, "411002003").await,
+            [
+                "SBIN0001110 - State Bank of India, SHIVAJINAGAR, SHIVAJINAGAR, PUNE 411005 (MICR 411002003)",
+                "1 of 2 branches shown",
+            ]
+        );
+        assert_eq!(
+            answer(&service, "999999999").await,
+            ["error: unknown MICR code 999999999"]
+        );
+    }
+
+    #[test]
+    fn branch_details_list_the_supported_rails() {
+        let ifsc = load(&[("KKBK.json", KKBK_BANK)]);
+        let payments = |code: &str| {
+            let lines = IfscService::format_branch(ifsc.get(code).unwrap());
+            (lines[8].clone(), lines[9].clone())
+        };
+
+        assert_eq!(
+            payments("KKBK0000001"),
+            (
+                "Payments: UPI, IMPS, NEFT, RTGS, SWIFT".to_string(),
+                "SWIFT: KKBKINBBXXX".to_string()
+            )
+        );
+        assert_eq!(
+            payments("KKBK0000002"),
+            ("Payments: NEFT".to_string(), "SWIFT: n/a".to_string())
+        );
+        assert_eq!(
+            payments("KKBK0000003"),
+            ("Payments: n/a".to_string(), "SWIFT: n/a".to_string())
+        );
+    }
+}
@@ -2,77 +2,137 @@ use std::net::IpAddr;
 
 use anyhow::Result;
 use async_trait::async_trait;
-use hickory_proto::rr::{Name, RData, Record, RecordType, rdata};
+use hickory_proto::rr::rdata::opt::ClientSubnet;
+use hickory_proto::rr::{Name, RecordType};
 use hickory_server::server::Request;
 
-use crate::handlers::Service;
+use crate::handlers::{Answer, DnsHandlers, ResponseBuilder, Service, Ttls};
 
-// Constants
-const IP_TTL: u32 = 60;
+/// Default TTL of ip answers
+pub const IP_TTL: u32 = 60;
 
 /// IP service that returns the client's IP address in various formats.
 ///
 /// This service provides IP echo functionality, allowing clients to discover
-/// their own IP address through DNS queries. It supports both TXT and A record
+/// their own IP address through DNS queries. It supports TXT, A and AAAA record
 /// types, returning the client's IP address in the appropriate format.
 ///
+/// IPv4-mapped IPv6 source addresses (`::ffff:a.b.c.d`), as seen by a dual-stack
+/// listener bound to `::`, are reported as the IPv4 address they carry.
+///
+/// Queries arriving through a public resolver come from the resolver, not the user.
+/// When the resolver forwards the user's subnet with EDNS Client Subnet (RFC 7871),
+/// the TXT answer also reports that subnet after the resolver address.
+///
 /// This is a "self-discovery" service - clients can query their own IP address
 /// through DNS, which is useful for network diagnostics, automation scripts,
 /// or determining external IP addresses from behind NAT/firewalls.
-pub struct IpService;
+pub struct IpService {
+    ttls: Ttls,
+}
 
 impl IpService {
     /// Creates a new IP service instance.
-    pub fn new() -> Self {
-        Self
+    ///
+    /// # Arguments
+    /// * `ttls` - TTLs of the returned records, per record type
+    pub fn new(ttls: Ttls) -> Self {
+        Self { ttls }
+    }
+
+    /// Describes a client address and its address family, as a single TXT string.
+    ///
+    /// IPv4 addresses also show their IPv4-mapped IPv6 form.
+    ///
+    /// # Example
+    /// ```ignore
+    /// assert_eq!(
+    ///     describe_address("192.0.2.1".parse()?),
+    ///     "192.0.2.1 (IPv4, IPv6-mapped ::ffff:192.0.2.1)"
+    /// );
+    /// assert_eq!(describe_address("2001:db8::1".parse()?), "2001:db8::1 (IPv6)");
+    /// ```
+    pub fn describe_address(client_ip: IpAddr) -> String {
+        match client_ip {
+            IpAddr::V4(ipv4) => format!("{} (IPv4, IPv6-mapped {})", ipv4, ipv4.to_ipv6_mapped()),
+            IpAddr::V6(ipv6) => format!("{} (IPv6)", ipv6),
+        }
+    }
+
+    /// Describes the EDNS Client Subnet a resolver sent, with the scope of the answer.
+    ///
+    /// # Example
+    /// ```ignore
+    /// assert_eq!(
+    ///     describe_client_subnet(&"203.0.113.0/24".parse()?),
+    ///     "resolver for client subnet 203.0.113.0/24 (source prefix /24, scope prefix /24)"
+    /// );
+    /// ```
+    pub fn describe_client_subnet(subnet: &ClientSubnet) -> String {
+        format!(
+            "resolver for client subnet {}/{} (source prefix /{}, scope prefix /{})",
+            subnet.addr(),
+            subnet.source_prefix(),
+            subnet.source_prefix(),
+            subnet.scope_prefix()
+        )
     }
 
     /// Handles IP queries, returning the client's IP address.
     ///
     /// This function provides IP echo functionality, allowing clients to discover
-    /// their own IP address through DNS queries. It supports both TXT and A record
-    /// types, returning the client's IP address in the appropriate format.
+    /// their own IP address through DNS queries. It supports TXT, A and AAAA record
+    /// types, returning the client's IP address in the appropriate format. TXT answers
+    /// hold the address and its address family in a single record, so `dig +short`
+    /// prints one line; when the query carries EDNS Client Subnet, the same record goes
+    /// on to report the client subnet the resolver (the source address) forwarded.
     ///
     /// ## Arguments
     /// * `request` - The DNS request, used to extract the client's source IP address
     /// * `query_name` - The DNS name being queried
-    /// * `query_type` - The type of DNS record requested (TXT or A)
+    /// * `query_type` - The type of DNS record requested (TXT, A or AAAA)
     ///
     /// ## Returns
-    /// * `Some(Record)` - DNS record containing the client's IP address
-    /// * `None` - If the query type is not supported or IPv6 is requested as A record
+    /// * `Some(Answer)` - DNS records containing the client's IP address
+    /// * `None` - If the query type is not supported or does not match the client's
+    ///   address family (e.g., A for an IPv6 client)
     pub async fn handle_ip_query(
         &self,
         request: &Request,
         query_name: &Name,
         query_type: RecordType,
-    ) -> Option<Record> {
-        if query_type != RecordType::TXT && query_type != RecordType::A {
-            return None;
-        }
+    ) -> Option<Answer> {
+        // Dual-stack listeners see IPv4 clients as ::ffff:a.b.c.d
+        let client_ip = request.src().ip().to_canonical();
+        let mut response = ResponseBuilder::new(query_name, &self.ttls);
 
-        let client_ip = request.src().ip();
-
-        match query_type {
-            RecordType::TXT => Some(Record::from_rdata(
-                query_name.clone(),
-                IP_TTL,
-                RData::TXT(rdata::TXT::new(vec![client_ip.to_string()])),
-            )),
-            RecordType::A => {
-                // Return as an A record if it's IPv4
-                if let IpAddr::V4(ipv4) = client_ip {
-                    Some(Record::from_rdata(
-                        query_name.clone(),
-                        IP_TTL,
-                        RData::A(ipv4.into()),
-                    ))
-                } else {
-                    None // Can't return IPv6 as A record
+        match (query_type, client_ip) {
+            (RecordType::TXT, _) => {
+                let mut text = Self::describe_address(client_ip);
+                if let Some(mut subnet) = DnsHandlers::client_subnet(request) {
+                    subnet.set_scope_prefix(self.client_subnet_scope(query_type, &subnet));
+                    text.push_str(", ");
+                    text.push_str(&Self::describe_client_subnet(&subnet));
                 }
+                response.txt(&text);
+            }
+            (RecordType::A, IpAddr::V4(ipv4)) => {
+                response.a(ipv4);
+            }
+            (RecordType::AAAA, IpAddr::V6(ipv6)) => {
+                response.aaaa(ipv6);
             }
-            _ => None,
+            // Can't return an address as a record of the other family
+            _ => return None,
         }
+
+        response.build()
+    }
+}
+
+impl Default for IpService {
+    fn default() -> Self {
+        Self::new(Ttls::new(IP_TTL))
     }
 }
 
@@ -91,7 +151,7 @@ impl Service for IpService {
     /// * `_cleaned_query` - Not used for IP service (IP is extracted from request)
     ///
     /// ## Returns
-    /// * `Some(Vec<Record>)` - Vector containing the client's IP address record
+    /// * `Some(Answer)` - Vector containing the client's IP address record
     /// * `None` - If the query type is not supported
     async fn query(
         &self,
@@ -99,11 +159,18 @@ impl Service for IpService {
         query_name: &Name,
         query_type: RecordType,
         _cleaned_query: &str,
-    ) -> Option<Vec<Record>> {
-        if let Some(record) = self.handle_ip_query(request, query_name, query_type).await {
-            Some(vec![record])
-        } else {
-            None
+    ) -> Option<Answer> {
+        self.handle_ip_query(request, query_name, query_type).await
+    }
+
+    /// Scopes TXT answers to the whole client subnet, since they report it.
+    ///
+    /// A and AAAA answers only hold the resolver's address, which is the same for every
+    /// client behind it, so they keep the default scope of 0.
+    fn client_subnet_scope(&self, query_type: RecordType, subnet: &ClientSubnet) -> u8 {
+        match query_type {
+            RecordType::TXT => subnet.source_prefix(),
+            _ => 0,
         }
     }
 
@@ -115,7 +182,125 @@ impl Service for IpService {
     /// * `Ok(Vec<u8>)` - Serialized service information
     /// * `Err(anyhow::Error)` - If serialization fails
     async fn dump(&self) -> Result<Vec<u8>> {
-        let service_info = "IP Service - Returns client IP address in TXT and A record formats";
+        let service_info =
+            "IP Service - Returns client IP address in TXT, A and AAAA record formats";
         Ok(service_info.as_bytes().to_vec())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::test_request_from;
+    use hickory_proto::op::Edns;
+    use hickory_proto::rr::RData;
+    use hickory_proto::rr::rdata::opt::EdnsOption;
+    use std::str::FromStr;
+
+    fn request(src: &str, query_type: RecordType, subnet: Option<ClientSubnet>) -> Request {
+        let edns = subnet.map(|subnet| {
+            let mut edns = Edns::new();
+            edns.options_mut().insert(EdnsOption::Subnet(subnet));
+            edns
+        });
+        let name = Name::from_str("ip.localhost.").unwrap();
+        test_request_from(src.parse().unwrap(), &name, query_type, edns)
+    }
+
+    async fn answer(src: &str, query_type: RecordType) -> Option<Vec<RData>> {
+        answer_request(request(src, query_type, None)).await
+    }
+
+    async fn answer_request(request: Request) -> Option<Vec<RData>> {
+        let query_type = request.queries()[0].query_type();
+        let name = Name::from_str("ip.localhost.").unwrap();
+        let records = IpService::default()
+            .handle_ip_query(&request, &name, query_type)
+            .await?;
+        Some(
+            records
+                .records
+                .into_iter()
+                .map(|record| record.data().clone())
+                .collect(),
+        )
+    }
+
+    fn txt(rdata: &RData) -> String {
+        match rdata {
+            RData::TXT(txt) => txt.to_string(),
+            other => panic!("expected TXT, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn describe_address_names_the_family() {
+        assert_eq!(
+            IpService::describe_address("192.0.2.1".parse().unwrap()),
+            "192.0.2.1 (IPv4, IPv6-mapped ::ffff:192.0.2.1)"
+        );
+        assert_eq!(
+            IpService::describe_address("2001:db8::1".parse().unwrap()),
+            "2001:db8::1 (IPv6)"
+        );
+    }
+
+    #[tokio::test]
+    async fn txt_is_a_single_record() {
+        let answers = answer("127.0.0.1:5353", RecordType::TXT).await.unwrap();
+        assert_eq!(answers.len(), 1);
+        assert_eq!(
+            txt(&answers[0]),
+            "127.0.0.1 (IPv4, IPv6-mapped ::ffff:127.0.0.1)"
+        );
+    }
+
+    #[tokio::test]
+    async fn mapped_ipv4_sources_are_reported_as_ipv4() {
+        let answers = answer("[::ffff:192.0.2.1]:5353", RecordType::A)
+            .await
+            .unwrap();
+        assert_eq!(answers, [RData::A("192.0.2.1".parse().unwrap())]);
+
+        let answers = answer("[::ffff:192.0.2.1]:5353", RecordType::TXT)
+            .await
+            .unwrap();
+        assert_eq!(
+            txt(&answers[0]),
+            "192.0.2.1 (IPv4, IPv6-mapped ::ffff:192.0.2.1)"
+        );
+    }
+
+    #[tokio::test]
+    async fn addresses_only_answer_their_own_family() {
+        let answers = answer("[2001:db8::1]:5353", RecordType::AAAA)
+            .await
+            .unwrap();
+        assert_eq!(answers, [RData::AAAA("2001:db8::1".parse().unwrap())]);
+
+        assert!(answer("[2001:db8::1]:5353", RecordType::A).await.is_none());
+        assert!(answer("192.0.2.1:5353", RecordType::AAAA).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn client_subnet_is_reported_in_the_same_record() {
+        let subnet = ClientSubnet::new("203.0.113.0".parse().unwrap(), 24, 0);
+        let request = request("192.0.2.53:5353", RecordType::TXT, Some(subnet));
+
+        let answers = answer_request(request).await.unwrap();
+        assert_eq!(answers.len(), 1);
+        assert_eq!(
+            txt(&answers[0]),
+            "192.0.2.53 (IPv4, IPv6-mapped ::ffff:192.0.2.53), resolver for client subnet \
+             203.0.113.0/24 (source prefix /24, scope prefix /24)"
+        );
+    }
+
+    #[test]
+    fn only_txt_answers_depend_on_the_client_subnet() {
+        let service = IpService::default();
+        let subnet = ClientSubnet::new("2001:db8::".parse().unwrap(), 48, 0);
+        assert_eq!(service.client_subnet_scope(RecordType::TXT, &subnet), 48);
+        assert_eq!(service.client_subnet_scope(RecordType::AAAA, &subnet), 0);
+    }
+}
@@ -1,50 +1,269 @@
+pub mod aerial;
+pub mod bank;
+pub mod country;
 pub mod geo;
+pub mod ifsc;
 pub mod ip;
 pub mod pi;
 pub mod random;
+pub mod sun;
+pub mod time;
 pub mod uuid;
 
-use crate::services::geo::GeoService;
+use crate::config::{Config, SoaConfig, ZoneConfig};
+use crate::handlers::{DnsHandlers, ResponseBuilder, Service, Ttls, Zone};
+use crate::ifsc::IFSC;
+use crate::services::aerial::AerialService;
+use crate::services::bank::BankService;
+use crate::services::country::{Countries, CountryService};
+use crate::services::geo::{Geo, GeoService, NameSources};
+use crate::services::ifsc::{IfscMode, IfscService};
 use crate::services::ip::IpService;
 use crate::services::pi::PiService;
+use crate::services::random::RandomService;
+use crate::services::sun::SunService;
+use crate::services::time::{TimeMode, TimeService};
 use crate::services::uuid::UUidService;
-use crate::{handlers::DnsHandlers, services::random::RandomService};
-use anyhow::Result;
-use hickory_proto::rr::{Name, RData, Record, rdata};
+use anyhow::{Context, Result, anyhow};
+use hickory_proto::rr::{LowerName, Name, Record, rdata};
+use std::collections::HashMap;
 use std::str::FromStr;
+use std::sync::Arc;
 
-// Constants
-const IP_TTL: u32 = 60;
+/// Default TTL of the help records
+pub const HELP_TTL: u32 = 60;
 
-/// Registers all available DNS services with the handlers.
+/// Returns the shared location index, loading it on first use.
+///
+/// The geo, time, aerial and sun services all query the same index, so it is only parsed once.
+fn shared_geo(geo: &mut Option<Arc<Geo>>, config: &Config) -> Result<Arc<Geo>> {
+    if let Some(geo) = geo {
+        return Ok(Arc::clone(geo));
+    }
+
+    let data_path = &config.geo.data_path;
+    let sources = NameSources {
+        inline: config.geo.alternate_names,
+        alternate_names_path: config.geo.alternate_names_path.clone(),
+        languages: config.geo.languages.clone(),
+        admin1_path: config.geo.admin1_path.clone(),
+    };
+    let snapshot_path = config.geo.snapshot_path.as_deref();
+    let loaded = Arc::new(
+        Geo::open(data_path, &sources, snapshot_path).with_context(|| {
+            format!(
+                "Failed to initialize geo service with data from '{}'",
+                data_path
+            )
+        })?,
+    );
+    tracing::info!("✅ Loaded {} locations from {}", loaded.count(), data_path);
+
+    *geo = Some(Arc::clone(&loaded));
+    Ok(loaded)
+}
+
+/// Returns the shared country data, loading it on first use.
+///
+/// The country service and the geo service (with `[geo] country_names`) read the same file,
+/// so it is only parsed once.
+fn shared_countries(
+    countries: &mut Option<Arc<Countries>>,
+    config: &Config,
+) -> Result<Arc<Countries>> {
+    if let Some(countries) = countries {
+        return Ok(Arc::clone(countries));
+    }
+
+    let data_path = &config.country.data_path;
+    let loaded = Arc::new(Countries::new(data_path)?);
+    tracing::info!("✅ Loaded {} countries from {}", loaded.count(), data_path);
+
+    *countries = Some(Arc::clone(&loaded));
+    Ok(loaded)
+}
+
+/// Returns the shared IFSC index, loading it on first use.
+///
+/// The ifsc, micr and bank suffixes query the same branch data, so it is only loaded once.
+fn shared_ifsc(ifsc: &mut Option<Arc<IFSC>>, config: &Config) -> Result<Arc<IFSC>> {
+    if let Some(ifsc) = ifsc {
+        return Ok(Arc::clone(ifsc));
+    }
+
+    let data_path = &config.ifsc.data_path;
+    let loaded = Arc::new(IFSC::new(data_path).with_context(|| {
+        format!(
+            "Failed to initialize ifsc service with data from '{}'",
+            data_path
+        )
+    })?);
+
+    *ifsc = Some(Arc::clone(&loaded));
+    Ok(loaded)
+}
+
+/// Builds every service referenced by at least one zone.
+///
+/// Services are created once and shared between zones, so data-heavy services such as
+/// geo are only loaded a single time even when several zones enable them.
+fn build_services(config: &Config) -> Result<HashMap<String, Arc<dyn Service>>> {
+    let mut services: HashMap<String, Arc<dyn Service>> = HashMap::new();
+    let mut geo = None;
+    let mut countries = None;
+    let mut ifsc = None;
+
+    for zone in &config.zones {
+        for suffix in &zone.services {
+            if services.contains_key(suffix) {
+                continue;
+            }
+
+            let service: Arc<dyn Service> = match suffix.as_str() {
+                "ip" => Arc::new(IpService::new(Ttls::from_config(
+                    config.ip.ttl,
+                    &config.ip.record_ttls,
+                )?)),
+                "uuid" => Arc::new(UUidService::new(
+                    config.uuid.max_results,
+                    Ttls::from_config(config.uuid.ttl, &config.uuid.record_ttls)?,
+                )),
+                "pi" => Arc::new(PiService::new(Ttls::from_config(
+                    config.pi.ttl,
+                    &config.pi.record_ttls,
+                )?)),
+                "geo" => Arc::new(GeoService::new(
+                    shared_geo(&mut geo, config)?,
+                    Ttls::from_config(config.geo.ttl, &config.geo.record_ttls)?,
+                    config.geo.nearest_results,
+                    config
+                        .geo
+                        .country_names
+                        .then(|| shared_countries(&mut countries, config))
+                        .transpose()?,
+                )),
+                "time" | "tz" => Arc::new(TimeService::new(
+                    shared_geo(&mut geo, config)?,
+                    if suffix == "tz" {
+                        TimeMode::Convert
+                    } else {
+                        TimeMode::Local
+                    },
+                    Ttls::from_config(config.time.ttl, &config.time.record_ttls)?,
+                )),
+                "aerial" => Arc::new(AerialService::new(
+                    shared_geo(&mut geo, config)?,
+                    Ttls::from_config(config.aerial.ttl, &config.aerial.record_ttls)?,
+                )),
+                "sun" => Arc::new(SunService::new(
+                    shared_geo(&mut geo, config)?,
+                    Ttls::from_config(config.sun.ttl, &config.sun.record_ttls)?,
+                )),
+                "country" => Arc::new(CountryService::new(
+                    shared_countries(&mut countries, config)?,
+                    Ttls::from_config(config.country.ttl, &config.country.record_ttls)?,
+                )),
+                "ifsc" | "micr" => Arc::new(IfscService::new(
+                    shared_ifsc(&mut ifsc, config)?,
+                    if suffix == "micr" {
+                        IfscMode::Micr
+                    } else {
+                        IfscMode::Ifsc
+                    },
+                    Ttls::from_config(config.ifsc.ttl, &config.ifsc.record_ttls)?,
+                    config.ifsc.max_results,
+                )),
+                "bank" => Arc::new(BankService::new(
+                    shared_ifsc(&mut ifsc, config)?,
+                    Ttls::from_config(config.ifsc.ttl, &config.ifsc.record_ttls)?,
+                )),
+                "random" => Arc::new(RandomService::new(Ttls::from_config(
+                    config.random.ttl,
+                    &config.random.record_ttls,
+                )?)),
+                _ => {
+                    return Err(anyhow!(
+                        "Unknown service '{}' enabled in zone '{}'",
+                        suffix,
+                        zone.domain
+                    ));
+                }
+            };
+            services.insert(suffix.clone(), service);
+            tracing::info!("✅ Created {} service", suffix);
+        }
+    }
+
+    Ok(services)
+}
+
+/// Builds the SOA rdata for a zone from its configuration.
+fn create_soa(domain: &Name, soa: &SoaConfig) -> Result<rdata::SOA> {
+    let mname = match &soa.mname {
+        Some(mname) => Name::from_str(mname)?,
+        None => Name::from_str("ns")?.append_domain(domain)?,
+    };
+    let rname = match &soa.rname {
+        Some(rname) => Name::from_str(rname)?,
+        None => Name::from_str("hostmaster")?.append_domain(domain)?,
+    };
+
+    Ok(rdata::SOA::new(
+        mname,
+        rname,
+        soa.serial,
+        soa.refresh,
+        soa.retry,
+        soa.expire,
+        soa.minimum,
+    ))
+}
+
+/// Creates a zone from its configuration, registering the enabled services.
+fn create_zone(
+    zone_config: &ZoneConfig,
+    services: &HashMap<String, Arc<dyn Service>>,
+) -> Result<Zone> {
+    let domain = Name::from_str(&zone_config.domain)?;
+    let soa = create_soa(&domain, &zone_config.soa)?;
+    let mut zone = Zone::new(LowerName::from(&domain), soa, zone_config.soa.ttl);
+
+    for suffix in &zone_config.services {
+        let service = services
+            .get(suffix)
+            .ok_or_else(|| anyhow!("Service '{}' was not created", suffix))?;
+        zone.register(suffix.clone(), Arc::clone(service));
+    }
+
+    zone.set_help_records(create_help_records(
+        &zone_config.domain,
+        &zone_config.services,
+        &zone_config.help,
+        zone_config.help_ttl,
+    )?);
+    zone.set_error_ttl(zone_config.error_ttl);
+
+    Ok(zone)
+}
+
+/// Registers all configured zones and their DNS services with the handlers.
 ///
 /// This function centralizes service registration, making it easy to add new services
-/// and test them individually. Currently registers: ip, uuid, pi, and geo services.
-pub fn register_services(handlers: &mut DnsHandlers) -> Result<()> {
-    // Register IP service
-    let ip_service = IpService::new();
-    handlers.register("ip".to_string(), Box::new(ip_service));
-    tracing::info!("✅ Registered IP service");
-
-    // Register UUID service
-    let uuid_service = UUidService::new(10); // Max 10 UUIDs per query
-    handlers.register("uuid".to_string(), Box::new(uuid_service));
-    tracing::info!("✅ Registered UUID service");
-
-    // Register Pi service
-    let pi_service = PiService::new();
-    handlers.register("pi".to_string(), Box::new(pi_service));
-    tracing::info!("✅ Registered Pi service");
-
-    // Register Geo service
-    let geo_service = GeoService::new("data/cities15000.txt")?;
-    handlers.register("geo".to_string(), Box::new(geo_service));
-    tracing::info!("✅ Registered Geo service");
-
-    // Register Random service
-    let random_service = RandomService::new();
-    handlers.register("random".to_string(), Box::new(random_service));
-    tracing::info!("✅ Registered Random service");
+/// and test them individually. Each zone only gets the services listed in its
+/// configuration. Currently supports: ip, uuid, pi, geo, time, tz, aerial, sun, country, ifsc,
+/// micr, bank and random services.
+pub fn register_services(handlers: &mut DnsHandlers, config: &Config) -> Result<()> {
+    let services = build_services(config)?;
+
+    for zone_config in &config.zones {
+        let zone = create_zone(zone_config, &services)?;
+        handlers.add_zone(zone);
+        tracing::info!(
+            "✅ Registered zone {} with services: {}",
+            zone_config.domain,
+            zone_config.services.join(", ")
+        );
+    }
 
     Ok(())
 }
@@ -53,7 +272,7 @@ pub fn register_services(handlers: &mut DnsHandlers) -> Result<()> {
 ///
 /// This function generates informative TXT records that users can query to learn
 /// about available services and how to use them. The records provide examples
-/// of valid DNS queries for each service.
+/// of valid DNS queries for each service enabled in the zone.
 ///
 /// This is the "user manual" generator - it creates DNS records that serve as
 /// documentation, helping users understand what services are available and how
@@ -61,29 +280,64 @@ pub fn register_services(handlers: &mut DnsHandlers) -> Result<()> {
 ///
 /// ## Arguments
 /// * `domain` - The domain name for which to generate help records
+/// * `services` - The service suffixes enabled in the zone
+/// * `extra` - Additional help lines configured for the zone
+/// * `ttl` - TTL of the help records
 ///
 /// ## Returns
 /// * `Ok(Vec<Record>)` - Vector of TXT records containing help information
 /// * `Err(anyhow::Error)` - If record creation fails
-pub fn create_help_records(domain: &str) -> Result<Vec<Record>> {
-    let help_texts = vec![
-        "Welcome! Available DNS services:".to_string(),
-        format!("dig TXT ip.{}", domain),
-        format!("dig A ip.{}", domain),
-        format!("dig A pi.{}", domain),
-        format!("dig TXT <location>.geo.{}", domain),
-        format!("dig TXT <number>.uuid.{}", domain),
-        format!("dig TXT <min>-<max>.random.{}", domain),
-        format!("dig TXT help.{}", domain),
-    ];
-    let mut records = Vec::new();
+pub fn create_help_records(
+    domain: &str,
+    services: &[String],
+    extra: &[String],
+    ttl: u32,
+) -> Result<Vec<Record>> {
+    let mut help_texts = vec!["Welcome! Available DNS services:".to_string()];
+    for service in services {
+        match service.as_str() {
+            "ip" => {
+                help_texts.push(format!("dig TXT ip.{}", domain));
+                help_texts.push(format!("dig A ip.{}", domain));
+                help_texts.push(format!("dig AAAA ip.{}", domain));
+            }
+            "pi" => help_texts.push(format!("dig A pi.{}", domain)),
+            "geo" => {
+                help_texts.push(format!("dig TXT <location>.geo.{}", domain));
+                help_texts.push(format!("dig LOC <location>.geo.{}", domain));
+                help_texts.push(format!("dig TXT <lat>,<lon>.geo.{}", domain));
+            }
+            "time" => help_texts.push(format!("dig TXT <city>[/<cc>].time.{}", domain)),
+            "tz" => help_texts.push(format!("dig TXT <hhmm>-<from>-<to>.tz.{}", domain)),
+            "aerial" => {
+                help_texts.push(format!("dig TXT <city>-<city>.aerial.{}", domain));
+                help_texts.push(format!("dig TXT <lat>,<lon>/<lat>,<lon>.aerial.{}", domain));
+            }
+            "sun" => {
+                help_texts.push(format!("dig TXT <city>.sun.{}", domain));
+                help_texts.push(format!("dig TXT <yyyy-mm-dd>-<city>.sun.{}", domain));
+            }
+            "country" => {
+                help_texts.push(format!("dig TXT <cc>.country.{}", domain));
+                help_texts.push(format!("dig TXT <name>.country.{}", domain));
+            }
+            "ifsc" => {
+                help_texts.push(format!("dig TXT <ifsc code>.ifsc.{}", domain));
+                help_texts.push(format!("dig TXT <bank code>-<city>.ifsc.{}", domain));
+            }
+            "micr" => help_texts.push(format!("dig TXT <micr code>.micr.{}", domain)),
+            "bank" => help_texts.push(format!("dig TXT <bank code>.bank.{}", domain)),
+            "uuid" => help_texts.push(format!("dig TXT <number>.uuid.{}", domain)),
+            "random" => help_texts.push(format!("dig TXT <min>-<max>.random.{}", domain)),
+            _ => {}
+        }
+    }
+    help_texts.push(format!("dig TXT help.{}", domain));
+    help_texts.extend(extra.iter().cloned());
+
+    let mut response = ResponseBuilder::new(&Name::from_str("help.")?, &Ttls::new(ttl));
     for text in help_texts {
-        let record = Record::from_rdata(
-            Name::from_str("help.")?.clone(),
-            IP_TTL,
-            RData::TXT(rdata::TXT::new(vec![text])),
-        );
-        records.push(record);
+        response.txt(&text);
     }
-    Ok(records)
+    Ok(response.into_records())
 }
@@ -1,8 +1,9 @@
-use crate::handlers::Service;
+use crate::handlers::{Answer, ResponseBuilder, Service, Ttls};
 use anyhow::Result;
 use async_trait::async_trait;
-use hickory_proto::rr::{Name, RData, Record, RecordType, rdata};
+use hickory_proto::rr::{Name, RecordType};
 use hickory_server::server::Request;
+use std::net::{Ipv4Addr, Ipv6Addr};
 
 /// PiService provides access to the mathematical constant Pi (π) through DNS queries.
 ///
@@ -13,15 +14,29 @@ use hickory_server::server::Request;
 ///
 /// The service is designed to be educational and demonstrate DNS-based data retrieval
 /// for mathematical constants.
-pub struct PiService;
+pub struct PiService {
+    ttls: Ttls,
+}
+
+/// Default TTL of pi answers, one year since π is not going to change
+pub const PI_TTL: u32 = 31536000;
 
-// Constants
-const PI_TTL: u32 = 31536000;
+/// Pi as text, with high precision
+const PI_TEXT: &str = "3.141592653589793238462643383279502884197169";
 
 impl PiService {
     /// Creates a new PiService instance.
-    pub fn new() -> Self {
-        Self
+    ///
+    /// # Arguments
+    /// * `ttls` - TTLs of the returned records, per record type
+    pub fn new(ttls: Ttls) -> Self {
+        Self { ttls }
+    }
+}
+
+impl Default for PiService {
+    fn default() -> Self {
+        Self::new(Ttls::new(PI_TTL))
     }
 }
 
@@ -44,7 +59,7 @@ impl Service for PiService {
     /// * `_cleaned_query` - The cleaned query string (unused for Pi service)
     ///
     /// ## Returns
-    /// * `Some(Vec<Record>)` - Vector containing Pi in the requested format
+    /// * `Some(Answer)` - Vector containing Pi in the requested format
     /// * `None` - If the query type is not supported
     async fn query(
         &self,
@@ -52,41 +67,22 @@ impl Service for PiService {
         query_name: &Name,
         query_type: RecordType,
         _cleaned_query: &str,
-    ) -> Option<Vec<Record>> {
+    ) -> Option<Answer> {
+        let mut response = ResponseBuilder::new(query_name, &self.ttls);
+
         match query_type {
             // Return Pi as text
-            RecordType::TXT => Some(vec![Record::from_rdata(
-                query_name.clone(),
-                PI_TTL,
-                RData::TXT(rdata::TXT::new(vec![
-                    "3.141592653589793238462643383279502884197169".to_string(),
-                ])),
-            )]),
-
-            RecordType::A => {
-                // Return Pi as IPv4: 3.141.59.27
-                let pi_ip = std::net::Ipv4Addr::new(3, 141, 59, 27);
-                Some(vec![Record::from_rdata(
-                    query_name.clone(),
-                    PI_TTL,
-                    RData::A(pi_ip.into()),
-                )])
-            }
+            RecordType::TXT => response.txt(PI_TEXT),
+            // Return Pi as IPv4: 3.141.59.27
+            RecordType::A => response.a(Ipv4Addr::new(3, 141, 59, 27)),
+            // Return Pi as IPv6: 3141:5926:5358:9793:2384:6264:3383:2795
+            RecordType::AAAA => response.aaaa(Ipv6Addr::new(
+                0x3141, 0x5926, 0x5358, 0x9793, 0x2384, 0x6264, 0x3383, 0x2795,
+            )),
+            _ => return None,
+        };
 
-            RecordType::AAAA => {
-                // Return Pi as IPv6: 3141:5926:5358:9793:2384:6264:3383:2795
-                if let Ok(pi_ipv6) = "3141:5926:5358:9793:2384:6264:3383:2795".parse() {
-                    Some(vec![Record::from_rdata(
-                        query_name.clone(),
-                        PI_TTL,
-                        RData::AAAA(pi_ipv6),
-                    )])
-                } else {
-                    None
-                }
-            }
-            _ => None,
-        }
+        response.build()
     }
 
     /// Exports raw service data for debugging or monitoring.
@@ -98,7 +94,6 @@ impl Service for PiService {
     /// * `Ok(Vec<u8>)` - Pi constant as bytes
     /// * `Err(anyhow::Error)` - If data export fails
     async fn dump(&self) -> Result<Vec<u8>> {
-        let pi_text = "3.141592653589793238462643383279502884197169";
-        Ok(pi_text.as_bytes().to_vec())
+        Ok(PI_TEXT.as_bytes().to_vec())
     }
 }
@@ -1,7 +1,7 @@
-use crate::handlers::Service;
+use crate::handlers::{Answer, DnsHandlers, ResponseBuilder, Service, Ttls};
 use anyhow::{Result, anyhow};
 use async_trait::async_trait;
-use hickory_proto::rr::{Name, RData, Record, RecordType, rdata};
+use hickory_proto::rr::{Name, RecordType};
 use hickory_server::server::Request;
 use once_cell::sync::Lazy;
 use rand::Rng;
@@ -17,9 +17,12 @@ use regex::Regex;
 /// - `dig TXT 10-50.random.localhost`
 ///
 /// The service is designed to be educational and demonstrate DNS-based random number generation.
-pub struct RandomService;
+pub struct RandomService {
+    ttls: Ttls,
+}
 
-const RANDOM_TTL: u32 = 1;
+/// Default TTL of random answers, kept short so every query gets a fresh number
+pub const RANDOM_TTL: u32 = 1;
 
 /// Regex to match a numeric range in the format "min-max".
 ///
@@ -30,8 +33,12 @@ static RANGE_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^([0-9]+)-([0-9]+)$").expect("Invalid Regex pattern"));
 
 impl RandomService {
-    pub fn new() -> Self {
-        Self
+    /// Creates a new RandomService instance.
+    ///
+    /// # Arguments
+    /// * `ttls` - TTLs of the returned records, per record type
+    pub fn new(ttls: Ttls) -> Self {
+        Self { ttls }
     }
 
     /// Generates a random integer within a specified range parsed from the query string.
@@ -49,22 +56,24 @@ impl RandomService {
     fn generate_random_number(&self, query: &str) -> Result<i32> {
         let captures = RANGE_REGEX
             .captures(query)
-            .ok_or_else(|| anyhow!("Invalid random query format"))?;
+            .ok_or_else(|| anyhow!("invalid random query, expected <min>-<max>"))?;
 
         let min_str = captures.get(1).unwrap().as_str();
         let max_str = captures.get(2).unwrap().as_str();
 
         let min: i32 = min_str
             .parse()
-            .map_err(|_| anyhow!("Invalid minimum value {}", min_str))?;
+            .map_err(|_| anyhow!("invalid minimum value {}", min_str))?;
         let max: i32 = max_str
             .parse()
-            .map_err(|_| anyhow!("Invalid maximum value {}", max_str))?;
+            .map_err(|_| anyhow!("invalid maximum value {}", max_str))?;
 
         if min > max {
-            return Err(anyhow!("Minimum value must be less than maximum value"));
+            return Err(anyhow!(
+                "minimum value must not be greater than maximum value"
+            ));
         } else if min < 0 || max < 0 {
-            return Err(anyhow!("Minimum and maximum values must be positive"));
+            return Err(anyhow!("minimum and maximum values must be positive"));
         }
 
         let random_value = rand::rng().random_range(min..=max);
@@ -72,6 +81,12 @@ impl RandomService {
     }
 }
 
+impl Default for RandomService {
+    fn default() -> Self {
+        Self::new(Ttls::new(RANDOM_TTL))
+    }
+}
+
 #[async_trait]
 impl Service for RandomService {
     /// Handles random number generation queries.
@@ -87,7 +102,8 @@ impl Service for RandomService {
     /// * `cleaned_query` - The cleaned query string (e.g., "1-100")
     ///
     /// ## Returns
-    /// * `Some(Vec<Record>)` - Vector containing the random number
+    /// * `Some(Answer)` - Vector containing the random number, or an error record if
+    ///   the range is invalid
     /// * `None` - If the query type is not supported
     async fn query(
         &self,
@@ -95,15 +111,21 @@ impl Service for RandomService {
         query_name: &Name,
         query_type: RecordType,
         cleaned_query: &str,
-    ) -> Option<Vec<Record>> {
+    ) -> Option<Answer> {
         match query_type {
             RecordType::TXT => {
-                let random_value = self.generate_random_number(cleaned_query).unwrap();
-                Some(vec![Record::from_rdata(
-                    query_name.clone(),
-                    RANDOM_TTL,
-                    RData::TXT(rdata::TXT::new(vec![random_value.to_string()])),
-                )])
+                let random_value = match self.generate_random_number(cleaned_query) {
+                    Ok(random_value) => random_value,
+                    Err(error) => {
+                        return Some(DnsHandlers::create_error_response(
+                            query_name,
+                            &error.to_string(),
+                        ));
+                    }
+                };
+                let mut response = ResponseBuilder::new(query_name, &self.ttls);
+                response.txt(&random_value.to_string());
+                response.build()
             }
             _ => None,
         }
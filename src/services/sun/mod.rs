@@ -0,0 +1,517 @@
+//! # Sun DNS Service
+//!
+//! This module provides sunrise, sunset, solar noon, civil twilight and day length for
+//! cities through DNS queries, using the locations and timezones loaded by the geo service.
+//!
+//! The solar position follows the NOAA solar calculator, which is based on Jean Meeus'
+//! "Astronomical Algorithms" and is accurate to about a minute for dates between 1900
+//! and 2100.
+
+use crate::handlers::{Answer, ResponseBuilder, Service, Ttls};
+use crate::services::geo::{Geo, Location};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, NaiveDate, TimeDelta, Utc};
+use chrono_tz::Tz;
+use hickory_proto::rr::{Name, RecordType};
+use hickory_server::server::Request;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::sync::Arc;
+
+/// Default TTL of sun answers, the times only change once a day
+pub const SUN_TTL: u32 = 300;
+
+/// Zenith angle of the sun's center at sunrise and sunset, accounting for atmospheric
+/// refraction and the radius of the solar disc
+const SUNRISE_ZENITH: f64 = 90.833;
+
+/// Zenith angle of the sun's center at the start and end of civil twilight
+const CIVIL_ZENITH: f64 = 96.0;
+
+/// Julian day of the J2000.0 epoch
+const J2000: f64 = 2_451_545.0;
+
+/// Julian day of the Unix epoch
+const UNIX_EPOCH_JULIAN_DAY: f64 = 2_440_587.5;
+
+/// Number of refinement passes when solving for the time of an event
+const REFINEMENT_PASSES: usize = 3;
+
+/// Years for which the algorithm is accurate enough to be served
+const SUPPORTED_YEARS: std::ops::RangeInclusive<i32> = 1900..=2100;
+
+/// Regex to match a query for a specific date in the format "yyyymmdd-city" or
+/// "yyyy-mm-dd-city".
+///
+/// For example, "2026-12-21-pune" captures "2026", "12", "21" and "pune".
+static DATE_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^([0-9]{4})-?([0-9]{2})-?([0-9]{2})-(.+)$").expect("Invalid Regex pattern")
+});
+
+/// When the sun crosses a given altitude on a day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Crossing {
+    /// The sun goes above the altitude and back below it again
+    Between(DateTime<Utc>, DateTime<Utc>),
+    /// The sun stays above the altitude all day (e.g., midnight sun)
+    AlwaysAbove,
+    /// The sun stays below the altitude all day (e.g., polar night)
+    AlwaysBelow,
+}
+
+/// Sun events of a single local day.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SunTimes {
+    /// Moment the sun crosses the local meridian
+    pub solar_noon: DateTime<Utc>,
+    /// Sunrise and sunset
+    pub daylight: Crossing,
+    /// Civil dawn and civil dusk
+    pub civil_twilight: Crossing,
+}
+
+/// Converts an instant to a Julian day.
+fn julian_day(instant: DateTime<Utc>) -> f64 {
+    instant.timestamp_millis() as f64 / 86_400_000.0 + UNIX_EPOCH_JULIAN_DAY
+}
+
+/// Computes the solar declination and the equation of time at an instant.
+///
+/// # Arguments
+/// * `instant` - The moment to compute the solar position for
+///
+/// # Returns
+/// * `(f64, f64)` - The declination in degrees and the equation of time in minutes
+pub fn solar_position(instant: DateTime<Utc>) -> (f64, f64) {
+    // Julian centuries since J2000.0
+    let t = (julian_day(instant) - J2000) / 36_525.0;
+
+    let mean_longitude = (280.466_46 + t * (36_000.769_83 + t * 0.000_303_2)).rem_euclid(360.0);
+    let mean_anomaly = 357.529_11 + t * (35_999.050_29 - 0.000_153_7 * t);
+    let eccentricity = 0.016_708_634 - t * (0.000_042_037 + 0.000_000_126_7 * t);
+
+    let m = mean_anomaly.to_radians();
+    let center = m.sin() * (1.914_602 - t * (0.004_817 + 0.000_014 * t))
+        + (2.0 * m).sin() * (0.019_993 - 0.000_101 * t)
+        + (3.0 * m).sin() * 0.000_289;
+    let true_longitude = mean_longitude + center;
+
+    let omega = (125.04 - 1_934.136 * t).to_radians();
+    let apparent_longitude = true_longitude - 0.005_69 - 0.004_78 * omega.sin();
+
+    let mean_obliquity =
+        23.0 + (26.0 + (21.448 - t * (46.815 + t * (0.000_59 - t * 0.001_813))) / 60.0) / 60.0;
+    let obliquity = (mean_obliquity + 0.002_56 * omega.cos()).to_radians();
+
+    let declination = (obliquity.sin() * apparent_longitude.to_radians().sin())
+        .asin()
+        .to_degrees();
+
+    let l0 = mean_longitude.to_radians();
+    let y = (obliquity / 2.0).tan().powi(2);
+    let equation_of_time = 4.0
+        * (y * (2.0 * l0).sin() - 2.0 * eccentricity * m.sin()
+            + 4.0 * eccentricity * y * m.sin() * (2.0 * l0).cos()
+            - 0.5 * y * y * (4.0 * l0).sin()
+            - 1.25 * eccentricity * eccentricity * (2.0 * m).sin())
+        .to_degrees();
+
+    (declination, equation_of_time)
+}
+
+/// Computes the hour angle at which the sun reaches a zenith angle.
+///
+/// # Returns
+/// * `Ok(f64)` - The hour angle in degrees
+/// * `Err(Crossing)` - [`Crossing::AlwaysAbove`] or [`Crossing::AlwaysBelow`] if the sun
+///   never reaches the zenith angle on that day
+fn hour_angle(latitude: f64, declination: f64, zenith: f64) -> Result<f64, Crossing> {
+    let (lat, decl) = (latitude.to_radians(), declination.to_radians());
+    let cos_h = zenith.to_radians().cos() / (lat.cos() * decl.cos()) - lat.tan() * decl.tan();
+
+    if cos_h > 1.0 {
+        Err(Crossing::AlwaysBelow)
+    } else if cos_h < -1.0 {
+        Err(Crossing::AlwaysAbove)
+    } else {
+        Ok(cos_h.acos().to_degrees())
+    }
+}
+
+/// Solves for the time of a sun event on the UTC day starting at `midnight`.
+///
+/// The solar position is recomputed at each estimate, so the event time converges
+/// within a few passes.
+///
+/// # Arguments
+/// * `midnight` - Start of the UTC day
+/// * `latitude`, `longitude` - The observer in decimal degrees
+/// * `zenith` - Zenith angle of the event, or None for solar noon
+/// * `direction` - `-1.0` for the morning event, `1.0` for the evening one
+fn solve_event(
+    midnight: DateTime<Utc>,
+    latitude: f64,
+    longitude: f64,
+    zenith: Option<f64>,
+    direction: f64,
+) -> Result<DateTime<Utc>, Crossing> {
+    let at_minutes = |minutes: f64| midnight + TimeDelta::milliseconds((minutes * 60_000.0) as i64);
+
+    let mut estimate = at_minutes(720.0 - 4.0 * longitude);
+    for _ in 0..REFINEMENT_PASSES {
+        let (declination, equation_of_time) = solar_position(estimate);
+        let offset = match zenith {
+            Some(zenith) => direction * 4.0 * hour_angle(latitude, declination, zenith)?,
+            None => 0.0,
+        };
+        estimate = at_minutes(720.0 - 4.0 * longitude - equation_of_time + offset);
+    }
+
+    Ok(estimate)
+}
+
+/// Computes when the sun crosses a zenith angle around the solar noon of a UTC day.
+fn crossing(midnight: DateTime<Utc>, latitude: f64, longitude: f64, zenith: f64) -> Crossing {
+    let rise = solve_event(midnight, latitude, longitude, Some(zenith), -1.0);
+    let set = solve_event(midnight, latitude, longitude, Some(zenith), 1.0);
+
+    match (rise, set) {
+        (Ok(rise), Ok(set)) => Crossing::Between(rise, set),
+        (Err(always), _) | (_, Err(always)) => always,
+    }
+}
+
+/// Computes the sun events of a local day at a place.
+///
+/// The UTC day is chosen so that its solar noon falls on the requested local date, which
+/// keeps places whose timezone is far from their mean solar time (e.g., Kiribati) on the
+/// right day.
+///
+/// # Arguments
+/// * `date` - The local date
+/// * `latitude`, `longitude` - The place in decimal degrees
+/// * `timezone` - The timezone of the place
+///
+/// # Returns
+/// * `SunTimes` - Solar noon, sunrise/sunset and civil twilight
+pub fn sun_times(date: NaiveDate, latitude: f64, longitude: f64, timezone: Tz) -> SunTimes {
+    let noon_of = |day: NaiveDate| {
+        let midnight = day.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+        let noon = solve_event(midnight, latitude, longitude, None, 0.0).unwrap_or(midnight);
+        (midnight, noon)
+    };
+
+    let (midnight, solar_noon) = [date.pred_opt(), Some(date), date.succ_opt()]
+        .into_iter()
+        .flatten()
+        .map(noon_of)
+        .find(|(_, noon)| noon.with_timezone(&timezone).date_naive() == date)
+        .unwrap_or_else(|| noon_of(date));
+
+    SunTimes {
+        solar_noon,
+        daylight: crossing(midnight, latitude, longitude, SUNRISE_ZENITH),
+        civil_twilight: crossing(midnight, latitude, longitude, CIVIL_ZENITH),
+    }
+}
+
+/// Formats a duration as hours and minutes (e.g., "11h 44m").
+fn format_duration(duration: TimeDelta) -> String {
+    let minutes = duration.num_minutes();
+    format!("{}h {:02}m", minutes / 60, minutes % 60)
+}
+
+/// DNS service returning sunrise, sunset and day length for a city.
+///
+/// Locations are resolved through [`Geo::query`], so `city`, `city/cc` and
+/// `city/cc/region` queries are supported (e.g., `dig pune.sun` or `dig london/gb.sun`).
+/// The times are for the current local date of each location, or for a given date when
+/// the query is prefixed with it (e.g., `dig 2026-12-21-pune.sun` or `dig 20261221-pune.sun`).
+/// Every matching location gets its own TXT record.
+pub struct SunService {
+    geo: Arc<Geo>,
+    ttls: Ttls,
+}
+
+impl SunService {
+    /// Creates a new SunService instance.
+    ///
+    /// # Arguments
+    /// * `geo` - The loaded location index, shared with the geo service
+    /// * `ttls` - TTLs of the returned records, per record type
+    pub fn new(geo: Arc<Geo>, ttls: Ttls) -> Self {
+        Self { geo, ttls }
+    }
+
+    /// Splits an optional date prefix off a query.
+    ///
+    /// # Returns
+    /// * `Some((Option<NaiveDate>, &str))` - The requested date, if any, and the location query
+    /// * `None` - If the date prefix is not a valid date within [`SUPPORTED_YEARS`]
+    fn parse_query(query: &str) -> Option<(Option<NaiveDate>, &str)> {
+        let Some(captures) = DATE_REGEX.captures(query) else {
+            return Some((None, query));
+        };
+
+        let year = captures.get(1)?.as_str().parse().ok()?;
+        let month = captures.get(2)?.as_str().parse().ok()?;
+        let day = captures.get(3)?.as_str().parse().ok()?;
+        let date = NaiveDate::from_ymd_opt(year, month, day)?;
+        if !SUPPORTED_YEARS.contains(&date.year()) {
+            return None;
+        }
+
+        Some((Some(date), captures.get(4)?.as_str()))
+    }
+
+    /// Formats the sun events of a location on a local date.
+    ///
+    /// # Arguments
+    /// * `location` - The location whose coordinates and timezone are used
+    /// * `date` - The local date
+    ///
+    /// # Returns
+    /// * `String` - Formatted sun information
+    ///
+    /// # Example
+    /// ```ignore
+    /// let txt = sun_service.format_sun_times(&pune, date);
+    /// assert_eq!(
+    ///     txt,
+    ///     "Pune (IN) - Sun, 18 Oct 2026: sunrise 06:29, sunset 18:10, solar noon 12:19, \
+    ///      civil twilight 06:07-18:32, day length 11h 40m (IST)"
+    /// );
+    /// ```
+    pub fn format_sun_times(&self, location: &Location, date: NaiveDate) -> String {
+        let times = sun_times(
+            date,
+            location.latitude,
+            location.longitude,
+            location.timezone,
+        );
+        let local = |instant: DateTime<Utc>| instant.with_timezone(&location.timezone);
+
+        let (daylight, day_length) = match times.daylight {
+            Crossing::Between(rise, set) => (
+                format!(
+                    "sunrise {}, sunset {}",
+                    local(rise).format("%H:%M"),
+                    local(set).format("%H:%M")
+                ),
+                format_duration(set - rise),
+            ),
+            Crossing::AlwaysAbove => ("midnight sun".to_string(), "24h 00m".to_string()),
+            Crossing::AlwaysBelow => ("polar night".to_string(), "0h 00m".to_string()),
+        };
+        let twilight = match times.civil_twilight {
+            Crossing::Between(dawn, dusk) => format!(
+                "{}-{}",
+                local(dawn).format("%H:%M"),
+                local(dusk).format("%H:%M")
+            ),
+            Crossing::AlwaysAbove => "all day".to_string(),
+            Crossing::AlwaysBelow => "none".to_string(),
+        };
+        let noon = local(times.solar_noon);
+
+        format!(
+            "{} ({}) - {}: {}, solar noon {}, civil twilight {}, day length {} ({})",
+            location.name,
+            location.country,
+            date.format("%a, %d %b %Y"),
+            daylight,
+            noon.format("%H:%M"),
+            twilight,
+            day_length,
+            noon.format("%Z")
+        )
+    }
+}
+
+#[async_trait]
+impl Service for SunService {
+    /// Handles sun queries, returning the sun events of each matching location.
+    ///
+    /// ## Arguments
+    /// * `request` - The DNS request
+    /// * `query_name` - The DNS name being queried
+    /// * `query_type` - The type of DNS record requested (only `TXT` is supported)
+    /// * `cleaned_query` - The cleaned query string (e.g., "pune" or "2026-12-21-pune")
+    ///
+    /// ## Returns
+    /// * `Some(Answer)` - One TXT record per matching location
+    /// * `None` - If the query type is not supported, the date is invalid or no location matches
+    async fn query(
+        &self,
+        _request: &Request,
+        query_name: &Name,
+        query_type: RecordType,
+        cleaned_query: &str,
+    ) -> Option<Answer> {
+        if query_type != RecordType::TXT {
+            return None;
+        }
+
+        let (date, location_query) = Self::parse_query(cleaned_query)?;
+        let locations = self.geo.query(location_query)?;
+        let now = Utc::now();
+
+        let mut response = ResponseBuilder::new(query_name, &self.ttls);
+        for location in locations {
+            let date = date.unwrap_or_else(|| now.with_timezone(&location.timezone).date_naive());
+            response.txt(&self.format_sun_times(location, date));
+        }
+        response.build()
+    }
+
+    /// Dumps service statistics for debugging purposes.
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>>` - Service summary as bytes
+    async fn dump(&self) -> Result<Vec<u8>> {
+        let summary = format!("Sun service backed by {} locations", self.geo.count());
+        Ok(summary.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::geo::LocationBuilder;
+
+    fn location(name: &str, latitude: f64, longitude: f64, timezone: Tz) -> Location {
+        LocationBuilder::new(name, name)
+            .coordinates(latitude, longitude)
+            .timezone(timezone)
+            .build()
+    }
+
+    fn service() -> SunService {
+        SunService::new(Arc::new(Geo::default()), Ttls::new(SUN_TTL))
+    }
+
+    fn date(text: &str) -> NaiveDate {
+        text.parse().unwrap()
+    }
+
+    /// Asserts that an instant is within a minute of the expected UTC time.
+    fn assert_near(actual: DateTime<Utc>, expected: &str) {
+        let expected: DateTime<Utc> = expected.parse().unwrap();
+        assert!(
+            (actual - expected).abs() <= TimeDelta::minutes(1),
+            "{} is not within a minute of {}",
+            actual,
+            expected
+        );
+    }
+
+    fn between(crossing: Crossing) -> (DateTime<Utc>, DateTime<Utc>) {
+        match crossing {
+            Crossing::Between(start, end) => (start, end),
+            other => panic!("expected a crossing, got {:?}", other),
+        }
+    }
+
+    // Reference values from the NOAA solar calculator
+
+    #[test]
+    fn london_summer_solstice_matches_noaa() {
+        let times = sun_times(date("2024-06-20"), 51.50853, -0.12574, Tz::Europe__London);
+
+        assert_near(times.solar_noon, "2024-06-20T12:02:12Z");
+        let (sunrise, sunset) = between(times.daylight);
+        assert_near(sunrise, "2024-06-20T03:43:01Z");
+        assert_near(sunset, "2024-06-20T20:21:23Z");
+        let (dawn, dusk) = between(times.civil_twilight);
+        assert_near(dawn, "2024-06-20T02:55:15Z");
+        assert_near(dusk, "2024-06-20T21:09:10Z");
+    }
+
+    #[test]
+    fn new_york_winter_solstice_matches_noaa() {
+        let times = sun_times(
+            date("2024-12-21"),
+            40.71427,
+            -74.00597,
+            Tz::America__New_York,
+        );
+
+        assert_near(times.solar_noon, "2024-12-21T16:54:27Z");
+        let (sunrise, sunset) = between(times.daylight);
+        assert_near(sunrise, "2024-12-21T12:16:56Z");
+        assert_near(sunset, "2024-12-21T21:31:58Z");
+    }
+
+    #[test]
+    fn polar_night_and_midnight_sun() {
+        let sun = service();
+        let tromso = location("Tromso", 69.6489, 18.95508, Tz::Europe__Oslo);
+
+        let winter = sun_times(date("2024-12-21"), 69.6489, 18.95508, Tz::Europe__Oslo);
+        assert_eq!(winter.daylight, Crossing::AlwaysBelow);
+        let (dawn, dusk) = between(winter.civil_twilight);
+        assert_near(dawn, "2024-12-21T08:31:33Z");
+        assert_near(dusk, "2024-12-21T12:53:25Z");
+        let txt = sun.format_sun_times(&tromso, date("2024-12-21"));
+        assert!(
+            txt.starts_with("Tromso (XX) - Sat, 21 Dec 2024: polar night, "),
+            "{}",
+            txt
+        );
+        assert!(txt.ends_with("day length 0h 00m (CET)"), "{}", txt);
+
+        let summer = sun_times(date("2024-06-21"), 69.6489, 18.95508, Tz::Europe__Oslo);
+        assert_eq!(summer.daylight, Crossing::AlwaysAbove);
+        assert_eq!(summer.civil_twilight, Crossing::AlwaysAbove);
+        let txt = sun.format_sun_times(&tromso, date("2024-06-21"));
+        assert!(txt.contains(": midnight sun, solar noon "), "{}", txt);
+        assert!(
+            txt.ends_with("civil twilight all day, day length 24h 00m (CEST)"),
+            "{}",
+            txt
+        );
+    }
+
+    #[test]
+    fn twilight_lasts_all_night_after_sunset() {
+        let sun = service();
+        let trondheim = location("Trondheim", 63.43049, 10.39506, Tz::Europe__Oslo);
+
+        let times = sun_times(date("2024-06-21"), 63.43049, 10.39506, Tz::Europe__Oslo);
+        let (sunrise, sunset) = between(times.daylight);
+        assert_near(sunrise, "2024-06-21T01:02:29Z");
+        assert_near(sunset, "2024-06-21T21:38:10Z");
+        assert_eq!(times.civil_twilight, Crossing::AlwaysAbove);
+
+        let txt = sun.format_sun_times(&trondheim, date("2024-06-21"));
+        assert!(txt.contains("civil twilight all day"), "{}", txt);
+        assert!(!txt.contains("midnight sun"), "{}", txt);
+    }
+
+    #[test]
+    fn date_prefix_is_limited_to_supported_years_and_real_dates() {
+        assert_eq!(SunService::parse_query("pune"), Some((None, "pune")));
+        assert_eq!(
+            SunService::parse_query("2026-12-21-pune"),
+            Some((Some(date("2026-12-21")), "pune"))
+        );
+        assert_eq!(
+            SunService::parse_query("20240229-new-york"),
+            Some((Some(date("2024-02-29")), "new-york"))
+        );
+        assert_eq!(
+            SunService::parse_query("1900-01-01-pune"),
+            Some((Some(date("1900-01-01")), "pune"))
+        );
+        assert_eq!(
+            SunService::parse_query("2100-12-31-pune"),
+            Some((Some(date("2100-12-31")), "pune"))
+        );
+
+        assert_eq!(SunService::parse_query("1899-12-31-pune"), None);
+        assert_eq!(SunService::parse_query("2101-01-01-pune"), None);
+        assert_eq!(SunService::parse_query("2023-02-30-pune"), None);
+        assert_eq!(SunService::parse_query("2023-13-01-pune"), None);
+    }
+}
@@ -0,0 +1,463 @@
+//! # Time DNS Service
+//!
+//! This module provides the current local time of cities and timezone conversions
+//! between two cities through DNS queries, using the locations and timezones loaded
+//! by the geo service.
+
+use crate::handlers::{Answer, DnsHandlers, ResponseBuilder, Service, Ttls};
+use crate::services::geo::{Geo, Location};
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, LocalResult, NaiveTime, TimeDelta, TimeZone, Utc};
+use chrono_tz::OffsetComponents;
+use hickory_proto::rr::{Name, RecordType};
+use hickory_server::server::Request;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::sync::Arc;
+
+/// Default TTL of time answers, kept short since the answer changes every second
+pub const TIME_TTL: u32 = 1;
+
+/// Maximum number of location pairs returned for a conversion query
+const MAX_CONVERSION_PAIRS: usize = 3;
+
+/// Regex to match a conversion query in the format "hhmm-from-to".
+///
+/// The time may be written as "1430", "930" or "14:30", and both cities accept the
+/// `city/cc` country filter. For example, "1430-mumbai-newyork" captures "14", "30",
+/// "mumbai" and "newyork".
+static CONVERT_REGEX: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^([0-9]{1,2}):?([0-9]{2})-([^-]+)-([^-]+)$").expect("Invalid Regex pattern")
+});
+
+/// Which queries a [`TimeService`] answers, depending on the suffix it is registered under.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeMode {
+    /// Current local time of a city (`time` suffix)
+    Local,
+    /// Conversion of a wall-clock time between two cities (`tz` suffix)
+    Convert,
+}
+
+/// DNS service returning the current local time for a city.
+///
+/// Locations are resolved through [`Geo::query`], so both `city` and `city/cc`
+/// queries are supported (e.g., `dig mumbai.time` or `dig london/gb.time`).
+/// Every matching location gets its own TXT record with the local time,
+/// UTC offset and daylight saving status.
+///
+/// Registered in [`TimeMode::Convert`], the service instead converts a wall-clock
+/// time between two cities (e.g., `dig 1430-mumbai-newyork.tz`), respecting DST on
+/// the current date.
+pub struct TimeService {
+    geo: Arc<Geo>,
+    mode: TimeMode,
+    ttls: Ttls,
+}
+
+impl TimeService {
+    /// Creates a new TimeService instance.
+    ///
+    /// # Arguments
+    /// * `geo` - The loaded location index, shared with the geo service
+    /// * `mode` - Which queries to answer
+    /// * `ttls` - TTLs of the returned records, per record type
+    pub fn new(geo: Arc<Geo>, mode: TimeMode, ttls: Ttls) -> Self {
+        Self { geo, mode, ttls }
+    }
+
+    /// Formats the local time of a location at the given instant.
+    ///
+    /// # Arguments
+    /// * `location` - The location whose timezone is used
+    /// * `now` - The instant to convert
+    ///
+    /// # Returns
+    /// * `String` - Formatted local time information
+    ///
+    /// # Example
+    /// ```ignore
+    /// let txt = time_service.format_location_time(&mumbai, Utc::now());
+    /// assert_eq!(
+    ///     txt,
+    ///     "Mumbai (IN, Asia/Kolkata) - Mon, 13 Oct 2025 14:30:05 IST, UTC+05:30, DST: no"
+    /// );
+    /// ```
+    pub fn format_location_time(&self, location: &Location, now: DateTime<Utc>) -> String {
+        let local = now.with_timezone(&location.timezone);
+        let is_dst = local.offset().dst_offset() != TimeDelta::zero();
+
+        format!(
+            "{} ({}, {}) - {}, UTC{}, DST: {}",
+            location.name,
+            location.country,
+            location.timezone_name,
+            local.format("%a, %d %b %Y %H:%M:%S %Z"),
+            local.format("%:z"),
+            if is_dst { "yes" } else { "no" }
+        )
+    }
+
+    /// Converts a wall-clock time from one location to another.
+    ///
+    /// The time is interpreted on the current date in the source timezone, so the
+    /// daylight saving rules in effect today apply to both ends of the conversion.
+    ///
+    /// # Arguments
+    /// * `time` - The wall-clock time at the source location
+    /// * `from` - The source location
+    /// * `to` - The destination location
+    /// * `now` - The current instant, used to pick the date
+    ///
+    /// # Returns
+    /// * `String` - Formatted conversion, or an error description if the time does not
+    ///   exist at the source (e.g., it falls into a DST gap)
+    ///
+    /// # Example
+    /// ```ignore
+    /// let txt = time_service.format_conversion(time, &mumbai, &new_york, Utc::now());
+    /// assert_eq!(
+    ///     txt,
+    ///     "14:30 IST Mumbai (IN) = 05:00 EDT New York City (US), Sun, 18 Oct 2026"
+    /// );
+    /// ```
+    pub fn format_conversion(
+        &self,
+        time: NaiveTime,
+        from: &Location,
+        to: &Location,
+        now: DateTime<Utc>,
+    ) -> String {
+        let date = now.with_timezone(&from.timezone).date_naive();
+        let source = match from.timezone.from_local_datetime(&date.and_time(time)) {
+            LocalResult::Single(source) => source,
+            // During the DST fall-back overlap, use the earlier (DST) instant
+            LocalResult::Ambiguous(earliest, _) => earliest,
+            LocalResult::None => {
+                return format!(
+                    "{} does not exist in {} ({}) on {}",
+                    time.format("%H:%M"),
+                    from.name,
+                    from.timezone_name,
+                    date.format("%a, %d %b %Y")
+                );
+            }
+        };
+        let target = source.with_timezone(&to.timezone);
+
+        format!(
+            "{} {} ({}) = {} {} ({}), {}",
+            source.format("%H:%M %Z"),
+            from.name,
+            from.country,
+            target.format("%H:%M %Z"),
+            to.name,
+            to.country,
+            target.format("%a, %d %b %Y")
+        )
+    }
+
+    /// Handles conversion queries in the format "hhmm-from-to".
+    ///
+    /// Both cities are resolved through [`Geo::query`]; pairs are formed from the most
+    /// populous matches first, up to [`MAX_CONVERSION_PAIRS`].
+    ///
+    /// # Arguments
+    /// * `query_name` - The DNS name being queried
+    /// * `query` - The cleaned conversion query
+    ///
+    /// # Returns
+    /// * `Option<Answer>` - TXT records with the conversion, or an error record if the
+    ///   query is malformed, the time is out of range or a city is unknown
+    fn handle_conversion_query(&self, query_name: &Name, query: &str) -> Option<Answer> {
+        let Some(captures) = CONVERT_REGEX.captures(query) else {
+            return Some(DnsHandlers::create_error_response(
+                query_name,
+                &format!(
+                    "malformed conversion '{}': expected <hhmm>-<from>-<to>",
+                    query
+                ),
+            ));
+        };
+        let hour = captures.get(1)?.as_str().parse().ok()?;
+        let minute = captures.get(2)?.as_str().parse().ok()?;
+        let Some(time) = NaiveTime::from_hms_opt(hour, minute, 0) else {
+            return Some(DnsHandlers::create_error_response(
+                query_name,
+                &format!(
+                    "invalid time in conversion '{}': expected 0000 to 2359",
+                    query
+                ),
+            ));
+        };
+
+        let unknown_city = |city: &str| {
+            Some(DnsHandlers::create_error_response(
+                query_name,
+                &format!("unknown city '{}' in conversion '{}'", city, query),
+            ))
+        };
+        let (from, to) = (captures.get(3)?.as_str(), captures.get(4)?.as_str());
+        let Some(sources) = self.geo.query(from) else {
+            return unknown_city(from);
+        };
+        let Some(targets) = self.geo.query(to) else {
+            return unknown_city(to);
+        };
+        let now = Utc::now();
+
+        let mut response = ResponseBuilder::new(query_name, &self.ttls);
+        let pairs = sources
+            .iter()
+            .flat_map(|from| targets.iter().map(move |to| (from, to)))
+            .take(MAX_CONVERSION_PAIRS);
+        for (from, to) in pairs {
+            response.txt(&self.format_conversion(time, from, to, now));
+        }
+        response.build()
+    }
+}
+
+#[async_trait]
+impl Service for TimeService {
+    /// Handles time queries, returning the current local time of each matching location,
+    /// or the converted time for "hhmm-from-to" queries in [`TimeMode::Convert`].
+    ///
+    /// ## Arguments
+    /// * `request` - The DNS request
+    /// * `query_name` - The DNS name being queried
+    /// * `query_type` - The type of DNS record requested (only `TXT` is supported)
+    /// * `cleaned_query` - The cleaned query string (e.g., "mumbai", "london/gb" or "1430-mumbai-newyork")
+    ///
+    /// ## Returns
+    /// * `Some(Answer)` - One TXT record per matching location, or an error record
+    ///   if the query has the shape of the other mode
+    /// * `None` - If the query type is not supported or no location matches
+    async fn query(
+        &self,
+        _request: &Request,
+        query_name: &Name,
+        query_type: RecordType,
+        cleaned_query: &str,
+    ) -> Option<Answer> {
+        if query_type != RecordType::TXT {
+            return None;
+        }
+
+        if self.mode == TimeMode::Convert {
+            return self.handle_conversion_query(query_name, cleaned_query);
+        }
+        if CONVERT_REGEX.is_match(cleaned_query) {
+            return Some(DnsHandlers::create_error_response(
+                query_name,
+                "time conversions are answered by the tz service",
+            ));
+        }
+
+        let locations = self.geo.query(cleaned_query)?;
+        let now = Utc::now();
+
+        let mut response = ResponseBuilder::new(query_name, &self.ttls);
+        for location in locations {
+            response.txt(&self.format_location_time(location, now));
+        }
+        response.build()
+    }
+
+    /// Dumps service statistics for debugging purposes.
+    ///
+    /// # Returns
+    /// * `Result<Vec<u8>>` - Service summary as bytes
+    async fn dump(&self) -> Result<Vec<u8>> {
+        let summary = format!("Time service backed by {} locations", self.geo.count());
+        Ok(summary.into_bytes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::handlers::test_request;
+    use crate::services::geo::{AlternateNames, LocationBuilder};
+    use chrono_tz::Tz;
+    use std::str::FromStr;
+
+    fn location(id: &str, name: &str, country: &str, timezone: Tz) -> Location {
+        LocationBuilder::new(id, name)
+            .timezone(timezone)
+            .country(country)
+            .build()
+    }
+
+    fn service(mode: TimeMode, locations: Vec<Location>) -> TimeService {
+        let mut geo = Geo::default();
+        geo.load(locations, &AlternateNames::new());
+        TimeService::new(Arc::new(geo), mode, Ttls::new(TIME_TTL))
+    }
+
+    fn cities() -> Vec<Location> {
+        vec![
+            location("1275339", "Mumbai", "IN", Tz::Asia__Kolkata),
+            location("2643743", "London", "GB", Tz::Europe__London),
+            location("4250542", "Springfield", "US", Tz::America__Chicago),
+            location("4409896", "Springfield", "US", Tz::America__Chicago),
+            location("5746545", "Portland", "US", Tz::America__Los_Angeles),
+            location("4975802", "Portland", "US", Tz::America__New_York),
+        ]
+    }
+
+    async fn answer(time: &TimeService, query: &str) -> Vec<String> {
+        let name = Name::from_str(&format!("{}.time.localhost.", query)).unwrap();
+        let request = test_request(&name, RecordType::TXT, None);
+
+        time.query(&request, &name, RecordType::TXT, query)
+            .await
+            .unwrap_or_default()
+            .records
+            .iter()
+            .map(|record| record.data().to_string())
+            .collect()
+    }
+
+    fn utc(text: &str) -> DateTime<Utc> {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn local_time_reports_the_offset_and_dst_flag() {
+        let time = service(TimeMode::Local, Vec::new());
+        let new_york = location("5128581", "New York City", "US", Tz::America__New_York);
+
+        assert_eq!(
+            time.format_location_time(&new_york, utc("2024-07-01T16:00:00Z")),
+            "New York City (US, America/New_York) - Mon, 01 Jul 2024 12:00:00 EDT, UTC-04:00, DST: yes"
+        );
+        assert_eq!(
+            time.format_location_time(&new_york, utc("2024-01-15T17:00:00Z")),
+            "New York City (US, America/New_York) - Mon, 15 Jan 2024 12:00:00 EST, UTC-05:00, DST: no"
+        );
+    }
+
+    #[test]
+    fn local_time_handles_fractional_offsets_and_date_changes() {
+        let time = service(TimeMode::Local, Vec::new());
+        let mumbai = location("1275339", "Mumbai", "IN", Tz::Asia__Kolkata);
+
+        assert_eq!(
+            time.format_location_time(&mumbai, utc("2024-07-01T20:15:30Z")),
+            "Mumbai (IN, Asia/Kolkata) - Tue, 02 Jul 2024 01:45:30 IST, UTC+05:30, DST: no"
+        );
+    }
+
+    #[test]
+    fn conversion_uses_the_earlier_instant_of_ambiguous_times() {
+        let time = service(TimeMode::Convert, Vec::new());
+        let new_york = location("5128581", "New York City", "US", Tz::America__New_York);
+        let london = location("2643743", "London", "GB", Tz::Europe__London);
+
+        // 01:30 happens twice on the fall-back date, first as EDT then as EST
+        assert_eq!(
+            time.format_conversion(
+                NaiveTime::from_hms_opt(1, 30, 0).unwrap(),
+                &new_york,
+                &london,
+                utc("2024-11-03T12:00:00Z")
+            ),
+            "01:30 EDT New York City (US) = 05:30 GMT London (GB), Sun, 03 Nov 2024"
+        );
+    }
+
+    #[test]
+    fn conversion_rejects_times_in_the_dst_gap() {
+        let time = service(TimeMode::Convert, Vec::new());
+        let new_york = location("5128581", "New York City", "US", Tz::America__New_York);
+        let london = location("2643743", "London", "GB", Tz::Europe__London);
+
+        assert_eq!(
+            time.format_conversion(
+                NaiveTime::from_hms_opt(2, 30, 0).unwrap(),
+                &new_york,
+                &london,
+                utc("2024-03-10T12:00:00Z")
+            ),
+            "02:30 does not exist in New York City (America/New_York) on Sun, 10 Mar 2024"
+        );
+    }
+
+    #[test]
+    fn conversion_regex_accepts_short_padded_and_colon_times() {
+        for (query, hour) in [("930-a-b", "9"), ("0930-a-b", "09"), ("09:30-a-b", "09")] {
+            let captures = CONVERT_REGEX.captures(query).unwrap();
+            assert_eq!(&captures[1], hour, "{}", query);
+            assert_eq!(&captures[2], "30", "{}", query);
+            assert_eq!((&captures[3], &captures[4]), ("a", "b"), "{}", query);
+        }
+
+        assert!(!CONVERT_REGEX.is_match("12345-a-b"));
+        assert!(!CONVERT_REGEX.is_match("930-a"));
+        assert!(!CONVERT_REGEX.is_match("930-a-b-c"));
+    }
+
+    #[tokio::test]
+    async fn conversion_queries_parse_short_times() {
+        let time = service(TimeMode::Convert, cities());
+
+        for query in ["930-mumbai-london", "0930-mumbai-london"] {
+            let answers = answer(&time, query).await;
+            assert_eq!(answers.len(), 1);
+            assert!(
+                answers[0].starts_with("09:30 IST Mumbai (IN) = "),
+                "{}",
+                answers[0]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn conversion_pairs_are_capped() {
+        let time = service(TimeMode::Convert, cities());
+
+        // Two matches on each side make four pairs
+        let answers = answer(&time, "1200-springfield-portland").await;
+        assert_eq!(answers.len(), MAX_CONVERSION_PAIRS);
+    }
+
+    #[tokio::test]
+    async fn each_suffix_rejects_the_other_query_shape() {
+        let local = service(TimeMode::Local, cities());
+        assert_eq!(
+            answer(&local, "1430-mumbai-london").await,
+            ["error: time conversions are answered by the tz service"]
+        );
+        assert!(answer(&local, "mumbai").await[0].starts_with("Mumbai (IN, Asia/Kolkata) - "));
+
+        let convert = service(TimeMode::Convert, cities());
+        assert_eq!(
+            answer(&convert, "mumbai").await,
+            ["error: malformed conversion 'mumbai': expected <hhmm>-<from>-<to>"]
+        );
+    }
+
+    #[tokio::test]
+    async fn conversion_rejects_out_of_range_times() {
+        let time = service(TimeMode::Convert, cities());
+        assert_eq!(
+            answer(&time, "2575-mumbai-london").await,
+            ["error: invalid time in conversion '2575-mumbai-london': expected 0000 to 2359"]
+        );
+    }
+
+    #[tokio::test]
+    async fn conversion_rejects_unknown_cities() {
+        let time = service(TimeMode::Convert, cities());
+        assert_eq!(
+            answer(&time, "1430-mumbai-atlantis").await,
+            ["error: unknown city 'atlantis' in conversion '1430-mumbai-atlantis'"]
+        );
+        assert_eq!(
+            answer(&time, "1430-atlantis-london").await,
+            ["error: unknown city 'atlantis' in conversion '1430-atlantis-london'"]
+        );
+    }
+}
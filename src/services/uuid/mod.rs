@@ -1,10 +1,10 @@
-use anyhow::{Ok, Result, anyhow};
+use anyhow::{Result, anyhow};
 use async_trait::async_trait;
 use std::str::FromStr;
 use uuid::Uuid;
 
-use crate::handlers::Service;
-use hickory_proto::rr::{Name, RData, Record, RecordType, rdata};
+use crate::handlers::{Answer, ResponseBuilder, Service, Ttls};
+use hickory_proto::rr::{Name, RecordType};
 use hickory_server::server::Request;
 
 /// UUID service that generates random UUIDs based on DNS queries.
@@ -14,19 +14,24 @@ use hickory_server::server::Request;
 /// it defaults to generating 1 UUID.
 pub struct UUidService {
     max_results: usize,
+    ttls: Ttls,
 }
 
+/// Default TTL of uuid answers
+pub const UUID_TTL: u32 = 60;
+
 impl UUidService {
     /// Creates a new UUID service with the specified maximum number of results.
     ///
     /// # Arguments
     /// * `max_results` - Maximum number of UUIDs that can be generated in a single query
+    /// * `ttls` - TTLs of the returned records, per record type
     ///
     /// # Returns
     /// A new `UuidService` instance
-    pub fn new(max_results: usize) -> Self {
+    pub fn new(max_results: usize, ttls: Ttls) -> Self {
         let max_results = if max_results < 1 { 1 } else { max_results };
-        Self { max_results }
+        Self { max_results, ttls }
     }
 
     /// Generates the specified number of UUIDs and formats them as strings.
@@ -72,29 +77,19 @@ impl Service for UUidService {
         query_name: &Name,
         query_type: RecordType,
         cleaned_query: &str,
-    ) -> Option<Vec<Record>> {
+    ) -> Option<Answer> {
         // UUID service only supports TXT records
         if query_type != RecordType::TXT {
             return None;
         }
 
         // Generate UUIDs based on the cleaned query
-        let uuids_result = self.generate_uuids(cleaned_query).await;
-        if uuids_result.is_ok() {
-            let uuids = uuids_result.unwrap();
-            let mut records = Vec::new();
-            for uuid in uuids {
-                let record = Record::from_rdata(
-                    query_name.clone(),
-                    60, // TTL
-                    RData::TXT(rdata::TXT::new(vec![uuid])),
-                );
-                records.push(record);
-            }
-            Some(records)
-        } else {
-            None
+        let uuids = self.generate_uuids(cleaned_query).await.ok()?;
+        let mut response = ResponseBuilder::new(query_name, &self.ttls);
+        for uuid in uuids {
+            response.txt(&uuid);
         }
+        response.build()
     }
 
     /// Export raw service data for debugging or monitoring.